@@ -1,4 +1,9 @@
 fn main() {
+    // Only the SDL window needs the libraries found at link and run time
+    if std::env::var_os("CARGO_FEATURE_GUI").is_none() {
+        return;
+    }
+
     #[cfg(target_os="macos")]
     println!("cargo:rustc-link-search=framework=/Library/Frameworks");
 
@@ -0,0 +1,55 @@
+// Counts move paths from a position, to check the move generator against known results:
+//   perft <depth> [FEN]    total leaf nodes
+//   divide <depth> [FEN]   leaf nodes under each legal move, then the total
+// The FEN defaults to the starting position and may be passed quoted or as separate words
+extern crate chess;
+
+use chess::perft::{divide, perft};
+use chess::position::Position;
+
+use std::env;
+use std::process::ExitCode;
+use std::time::Instant;
+
+const USAGE: &str = "usage: perft <depth> [FEN] | divide <depth> [FEN]";
+
+fn run(args: &[String]) -> Result<(), String> {
+    let (mode, depth) = match args {
+        [mode, depth, ..] if mode == "perft" || mode == "divide" => (mode, depth),
+        _ => return Err(USAGE.to_string()),
+    };
+    let depth: u32 = depth.parse().map_err(|_| format!("invalid depth: {depth}\n{USAGE}"))?;
+    let position = match &args[2..] {
+        [] => Position::new(),
+        fen => Position::from_fen(&fen.join(" ")).map_err(|e| format!("invalid fen: {e}"))?,
+    };
+
+    let start = Instant::now();
+    let nodes = if mode == "divide" {
+        let counts = divide(&position, depth);
+        for (mv, count) in &counts {
+            println!("{}: {count}", mv.uci());
+        }
+        println!();
+        println!("Moves: {}", counts.len());
+        counts.iter().map(|(_, count)| count).sum()
+    } else {
+        perft(&position, depth)
+    };
+
+    let elapsed = start.elapsed();
+    println!("Nodes: {nodes}");
+    println!("Time: {} ms ({:.0} nodes/s)", elapsed.as_millis(), nodes as f64 / elapsed.as_secs_f64().max(f64::EPSILON));
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
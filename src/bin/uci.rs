@@ -0,0 +1,176 @@
+// Speaks the UCI protocol over stdin/stdout so chess GUIs can use the engine
+extern crate chess;
+
+use chess::pieces::{Move, PieceColor};
+use chess::position::Position;
+use chess::search::{search, Limits, Report, MATE_SCORE, MAX_DEPTH};
+use chess::transposition::{TranspositionTable, DEFAULT_MEGABYTES};
+
+use std::io::{self, BufRead};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+// Time kept back for the GUI and the pipe when working out how long to think
+const MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+
+// Moves the rest of the game is assumed to take when the GUI doesn't send movestogo
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+// Largest transposition table the Hash option allows, in megabytes
+const MAX_HASH: usize = 1024;
+
+// How often a finished `go infinite` search looks for the stop it has to wait for
+const STOP_POLL: Duration = Duration::from_millis(5);
+
+// Sets up the position from `startpos` or `fen <FEN>`, then plays the moves listed after `moves`.
+// Also returns the hashes of the positions passed through on the way, for repetition detection
+fn parse_position(args: &[&str]) -> Result<(Position, Vec<u64>), String> {
+    let moves_at = args.iter().position(|arg| *arg == "moves").unwrap_or(args.len());
+    let mut position = match args.first() {
+        Some(&"startpos") => Position::new(),
+        Some(&"fen") => Position::from_fen(&args[1..moves_at].join(" ")).map_err(|e| format!("invalid fen: {e}"))?,
+        _ => return Err("expected startpos or fen".to_string()),
+    };
+
+    let mut history = vec![];
+    for uci in args.iter().skip(moves_at + 1) {
+        let mv = Move::from_uci(&position, uci).map_err(|e| e.to_string())?;
+        history.push(position.hash);
+        position.play_move(&mv)?;
+    }
+    Ok((position, history))
+}
+
+// Reads the limits of a `go` command. With only clock times given, thinks for an even share of the time left
+fn parse_go(args: &[&str], side_to_move: PieceColor) -> Limits {
+    let value = |name: &str| -> Option<u64> {
+        let at = args.iter().position(|arg| *arg == name)?;
+        args.get(at + 1)?.parse().ok()
+    };
+
+    let mut limits = Limits { depth: value("depth").map(|depth| depth as u32), movetime: value("movetime").map(Duration::from_millis) };
+    if args.contains(&"infinite") {
+        return limits;
+    }
+
+    let (time, increment) = match side_to_move {
+        PieceColor::White => (value("wtime"), value("winc")),
+        PieceColor::Black => (value("btime"), value("binc")),
+    };
+    if let (None, Some(time)) = (limits.movetime, time) {
+        let moves_to_go = value("movestogo").map_or(DEFAULT_MOVES_TO_GO, |moves| moves.max(1) as u32);
+        let time = Duration::from_millis(time);
+        let share = time / moves_to_go + Duration::from_millis(increment.unwrap_or(0)) / 2;
+        limits.movetime = Some(share.min(time.saturating_sub(MOVE_OVERHEAD)).max(Duration::from_millis(1)));
+    }
+    limits
+}
+
+fn print_info(report: &Report) {
+    // Mate scores are given in moves, negative when the engine is the one being mated
+    let score = if report.score.abs() >= MATE_SCORE - MAX_DEPTH as i32 {
+        let moves = (MATE_SCORE - report.score.abs() + 1) / 2;
+        format!("mate {}", if report.score > 0 { moves } else { -moves })
+    } else {
+        format!("cp {}", report.score)
+    };
+    println!(
+        "info depth {} score {score} nodes {} time {} pv {}",
+        report.depth,
+        report.nodes,
+        report.elapsed.as_millis(),
+        report.best_move.uci()
+    );
+}
+
+// Handles `setoption name Hash value <MB>`, the only option the engine has
+fn set_option(args: &[&str], table: &Mutex<TranspositionTable>) -> Result<(), String> {
+    match args {
+        ["name", name, "value", value] if name.eq_ignore_ascii_case("Hash") => {
+            let megabytes: usize = value.parse().map_err(|_| format!("invalid Hash value: {value}"))?;
+            *table.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = TranspositionTable::new(megabytes.clamp(1, MAX_HASH));
+            Ok(())
+        }
+        _ => Err(format!("unknown option: {}", args.join(" "))),
+    }
+}
+
+// Stops a running search and waits for it to print its bestmove
+fn stop_search(stop: &AtomicBool, searcher: &mut Option<JoinHandle<()>>) {
+    if let Some(handle) = searcher.take() {
+        stop.store(true, Ordering::Relaxed);
+        let _ = handle.join();
+    }
+}
+
+fn main() {
+    let mut position = Position::new();
+    let mut history: Vec<u64> = vec![];
+    let table = Arc::new(Mutex::new(TranspositionTable::default()));
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut searcher: Option<JoinHandle<()>> = None;
+
+    for line in io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+
+        match tokens.first() {
+            Some(&"uci") => {
+                println!("id name {} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+                println!("id author nbybhen");
+                println!("option name Hash type spin default {DEFAULT_MEGABYTES} min 1 max {MAX_HASH}");
+                println!("uciok");
+            }
+            Some(&"isready") => println!("readyok"),
+            Some(&"ucinewgame") => {
+                stop_search(&stop, &mut searcher);
+                position = Position::new();
+                history.clear();
+                table.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clear();
+            }
+            Some(&"setoption") => {
+                stop_search(&stop, &mut searcher);
+                if let Err(e) = set_option(&tokens[1..], &table) {
+                    println!("info string {e}");
+                }
+            }
+            Some(&"position") => {
+                stop_search(&stop, &mut searcher);
+                match parse_position(&tokens[1..]) {
+                    Ok((new_position, new_history)) => (position, history) = (new_position, new_history),
+                    Err(e) => println!("info string {e}"),
+                }
+            }
+            Some(&"go") => {
+                stop_search(&stop, &mut searcher);
+                let limits = parse_go(&tokens[1..], position.side_to_move);
+                let infinite = tokens.contains(&"infinite");
+                let (root, history) = (position, history.clone());
+                let table = Arc::clone(&table);
+                let stop = Arc::clone(&stop);
+                stop.store(false, Ordering::Relaxed);
+                searcher = Some(thread::spawn(move || {
+                    let mut table = table.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    let best_move = search(&root, &history, limits, &mut table, &stop, print_info);
+                    // An infinite search may run out of depth or find a mate early, but the protocol
+                    // only allows bestmove once the GUI has sent stop (or quit)
+                    while infinite && !stop.load(Ordering::Relaxed) {
+                        thread::sleep(STOP_POLL);
+                    }
+                    // "0000" is the null move, sent when there is nothing legal to play
+                    println!("bestmove {}", best_move.map_or("0000".to_string(), |mv| mv.uci()));
+                }));
+            }
+            Some(&"stop") => stop_search(&stop, &mut searcher),
+            Some(&"quit") => break,
+            // Unknown commands are ignored, as the protocol asks
+            _ => {}
+        }
+    }
+    stop_search(&stop, &mut searcher);
+}
@@ -0,0 +1,231 @@
+use crate::pieces::Point;
+
+use std::sync::OnceLock;
+
+// One bit per square, bit n being the square with Point::index() == n
+pub type Bitboard = u64;
+
+// North, South, East, West
+pub const ROOK_DIRECTIONS: [(i32, i32); 4] = [(0, -1), (0, 1), (1, 0), (-1, 0)];
+// North-west, North-east, South-east, South-west
+pub const BISHOP_DIRECTIONS: [(i32, i32); 4] = [(-1, -1), (1, -1), (1, 1), (-1, 1)];
+pub const KNIGHT_JUMPS: [(i32, i32); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+pub const KING_STEPS: [(i32, i32); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+
+// Squares where x + y is even, which are the light ones (a1, on x = 7 and y = 0, is dark)
+pub const LIGHT_SQUARES: Bitboard = 0xAA55_AA55_AA55_AA55;
+
+pub const KNIGHT_ATTACKS: [Bitboard; 64] = leaper_table(&KNIGHT_JUMPS);
+pub const KING_ATTACKS: [Bitboard; 64] = leaper_table(&KING_STEPS);
+// Indexed by PieceColor::index(), White pawns attack towards y + 1 and Black pawns towards y - 1
+pub const PAWN_ATTACKS: [[Bitboard; 64]; 2] = [leaper_table(&[(-1, 1), (1, 1)]), leaper_table(&[(-1, -1), (1, -1)])];
+
+// Builds the attack table of a piece that jumps by fixed offsets
+const fn leaper_table(offsets: &[(i32, i32)]) -> [Bitboard; 64] {
+    let mut table = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let (x, y) = ((square % 8) as i32, (square / 8) as i32);
+        let mut i = 0;
+        while i < offsets.len() {
+            let (tx, ty) = (x + offsets[i].0, y + offsets[i].1);
+            if tx >= 0 && tx < 8 && ty >= 0 && ty < 8 {
+                table[square] |= 1 << (ty * 8 + tx);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+pub fn bit(point: &Point) -> Bitboard {
+    1 << point.index()
+}
+
+// Iterates over the points of every set bit, lowest index first
+pub fn points(mut bitboard: Bitboard) -> impl Iterator<Item = Point> {
+    std::iter::from_fn(move || {
+        if bitboard == 0 {
+            return None;
+        }
+        let index = bitboard.trailing_zeros() as usize;
+        bitboard &= bitboard - 1;
+        Some(Point::from_index(index))
+    })
+}
+
+pub fn rook_attacks(square: usize, occupied: Bitboard) -> Bitboard {
+    let tables = sliding_tables();
+    tables.table[tables.rook[square].index(occupied)]
+}
+
+pub fn bishop_attacks(square: usize, occupied: Bitboard) -> Bitboard {
+    let tables = sliding_tables();
+    tables.table[tables.bishop[square].index(occupied)]
+}
+
+pub fn queen_attacks(square: usize, occupied: Bitboard) -> Bitboard {
+    rook_attacks(square, occupied) | bishop_attacks(square, occupied)
+}
+
+// Attacks of a sliding piece found by walking each ray until it hits a piece (only used to fill the magic tables)
+fn slow_sliding_attacks(square: usize, occupied: Bitboard, directions: &[(i32, i32); 4]) -> Bitboard {
+    let mut attacks = 0;
+    for (dx, dy) in directions {
+        let mut current = Point::from_index(square).offset(*dx, *dy);
+        while let Some(point) = current {
+            attacks |= bit(&point);
+            if occupied & bit(&point) != 0 {
+                break;
+            }
+            current = point.offset(*dx, *dy);
+        }
+    }
+    attacks
+}
+
+// Squares whose occupancy changes the attacks from the square (board edges never block anything further)
+fn relevant_mask(square: usize, directions: &[(i32, i32); 4]) -> Bitboard {
+    let mut mask = 0;
+    for (dx, dy) in directions {
+        let mut current = Point::from_index(square).offset(*dx, *dy);
+        while let Some(point) = current {
+            current = point.offset(*dx, *dy);
+            if current.is_some() {
+                mask |= bit(&point);
+            }
+        }
+    }
+    mask
+}
+
+// Maps every blocker layout of one square to its slot in the shared attack table
+#[derive(Clone, Copy, Default)]
+struct Magic {
+    mask: Bitboard,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    fn index(&self, occupied: Bitboard) -> usize {
+        self.offset + (((occupied & self.mask).wrapping_mul(self.magic)) >> self.shift) as usize
+    }
+}
+
+struct SlidingTables {
+    rook: [Magic; 64],
+    bishop: [Magic; 64],
+    table: Vec<Bitboard>,
+}
+
+static SLIDING_TABLES: OnceLock<SlidingTables> = OnceLock::new();
+
+// Magic numbers found by find_magics for this board layout (bit n is the square with Point::index() == n,
+// so the usual published magics, made for a1 = bit 0, don't work here). Saves the slow search at startup
+const ROOK_MAGICS: [u64; 64] = [
+    0x1080_0040_0880_1020, 0x0840_0920_02C0_3000, 0x1900_2000_1040_0900, 0x0880_1000_0800_0480,
+    0x4200_1004_2008_0200, 0x8100_0201_0008_0400, 0x0200_0401_1088_6200, 0x0200_0080_4022_0411,
+    0x0404_8000_8440_0220, 0x0000_4010_0040_2000, 0x0086_0010_8122_0440, 0x0408_8008_0010_0280,
+    0x000A_0012_0104_0820, 0x8848_8002_0084_0080, 0x4001_0001_0004_0200, 0x0442_0001_0210_5084,
+    0x9080_0100_2080_4100, 0x0040_4040_0020_1009, 0x0000_8080_1000_2009, 0x2200_0900_21D0_0100,
+    0x0008_0080_0804_0080, 0x0004_0040_0201_0040, 0x0011_0400_0801_5042, 0x0000_0A00_0176_8104,
+    0x0000_8000_8020_4009, 0x2010_0041_4000_2001, 0x9800_2002_8010_0080, 0x1000_1000_8008_0080,
+    0x0442_000A_0004_9020, 0x2100_0400_8002_0080, 0x0800_1204_0090_0148, 0x0010_040A_0012_8541,
+    0x2800_8040_0080_0030, 0x1010_0020_0040_0041, 0x4000_2000_1100_4100, 0x0610_0084_1080_0800,
+    0x0400_8024_0280_0800, 0xC100_0200_8080_0400, 0x0002_0008_0200_0401, 0x0182_0858_8200_0401,
+    0x0220_2040_0080_8000, 0x2860_1000_4002_4022, 0x0001_0020_0411_0040, 0x9910_1042_000A_0020,
+    0x0004_0800_0400_8080, 0x0010_0400_0200_8080, 0x2012_0048_8102_0004, 0x8300_8424_4482_0011,
+    0x0088_4038_8201_0200, 0x0820_4000_8021_0100, 0x0110_9100_40A0_0300, 0x0801_1002_8008_0480,
+    0x0242_0090_0820_0600, 0x1002_0004_8950_0200, 0x0040_8002_0001_0080, 0x0091_8000_4100_0080,
+    0x0000_2093_0048_8001, 0x04C1_0024_1482_4001, 0x0200_2000_0B00_1041, 0x7000_1000_0420_0901,
+    0x8002_0020_0410_0802, 0x3001_0002_084C_0007, 0x0888_2218_0081_3004, 0x4000_0028_4084_0112,
+];
+const BISHOP_MAGICS: [u64; 64] = [
+    0x1010_2002_004A_1420, 0x8020_0404_0058_4008, 0x1051_0800_8112_01C8, 0x5204_0420_8000_0088,
+    0x2204_1068_8000_0002, 0x1401_0420_0400_0000, 0x0400_8804_1004_2004, 0x0028_2082_00A0_2020,
+    0x1500_2419_9001_0E00, 0x8001_2001_8202_0A40, 0x4000_4101_030B_0000, 0x8002_0410_4200_0100,
+    0x4010_0110_4102_0038, 0x0000_0104_2104_4000, 0x1500_2108_0802_0A00, 0x8000_0884_0088_0520,
+    0x0405_0040_1004_0100, 0x1005_8232_1004_0108, 0x2708_0081_0204_0011, 0x4048_2004_0400_9100,
+    0x0018_1041_0140_0024, 0x0003_0006_0119_0101, 0x8004_8031_0849_1000, 0x8014_2412_0082_0800,
+    0x0006_E080_100C_3040, 0x0501_044A_1104_1800, 0x9020_3000_0800_4045, 0x0894_0800_0022_0040,
+    0x1001_0100_8310_4000, 0x5004_0300_4090_0080, 0x0004_0042_2C01_2400, 0x0002_1286_9840_4812,
+    0x1010_1084_0490_0440, 0x0928_0211_8208_4100, 0x2006_0804_0902_0024, 0x1010_2020_2018_0080,
+    0xA010_0082_0020_2200, 0x2098_0151_0001_9004, 0x0002_0414_4081_0811, 0x802A_0202_0000_B098,
+    0x0009_0150_9000_4060, 0x4000_8210_8208_1001, 0x0100_2100_4042_0800, 0x0800_0040_1048_8A00,
+    0x2000_0811_0400_4040, 0x4C8E_0290_1500_0082, 0x0420_3403_2222_4842, 0x1298_2600_4340_0210,
+    0x0000_8228_0240_0008, 0x0000_8A01_0160_0000, 0x3040_0034_1208_0021, 0x3040_2902_2088_4800,
+    0x4A15_0040_1041_004A, 0x8010_2002_8202_0781, 0x0020_2031_4220_9091, 0x0070_3006_0090_2110,
+    0x0040_8088_00B6_2048, 0x0000_8104_00C4_4420, 0x0008_0400_440C_0441, 0x8340_0800_2084_0411,
+    0x0000_0001_0420_8200, 0x0000_8008_10D0_0080, 0x0400_5304_1108_0200, 0x4040_7024_0093_2244,
+];
+
+fn sliding_tables() -> &'static SlidingTables {
+    SLIDING_TABLES.get_or_init(|| {
+        let mut table: Vec<Bitboard> = Vec::with_capacity(102400 + 5248);
+        let rook = find_magics(&ROOK_DIRECTIONS, &ROOK_MAGICS, &mut table);
+        let bishop = find_magics(&BISHOP_DIRECTIONS, &BISHOP_MAGICS, &mut table);
+        SlidingTables { rook, bishop, table }
+    })
+}
+
+// Finds a magic number per square that sends every blocker layout to a slot holding the right attacks,
+// appending each square's slots to the table. The known magic is tried first, and a random search only runs
+// if it doesn't fit (the seed is fixed, so the search finds the same magics on every run)
+fn find_magics(directions: &[(i32, i32); 4], known: &[u64; 64], table: &mut Vec<Bitboard>) -> [Magic; 64] {
+    let mut magics = [Magic::default(); 64];
+    let mut seed: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut random = move || {
+        // xorshift64*
+        seed ^= seed >> 12;
+        seed ^= seed << 25;
+        seed ^= seed >> 27;
+        seed.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    };
+
+    for (square, magic) in magics.iter_mut().enumerate() {
+        let mask = relevant_mask(square, directions);
+        let bits = mask.count_ones();
+
+        // Every subset of the mask (Carry-Rippler trick) and the attacks it produces
+        let mut occupancies: Vec<Bitboard> = Vec::with_capacity(1 << bits);
+        let mut subset: Bitboard = 0;
+        loop {
+            occupancies.push(subset);
+            subset = subset.wrapping_sub(mask) & mask;
+            if subset == 0 {
+                break;
+            }
+        }
+        let attacks: Vec<Bitboard> = occupancies.iter().map(|occ| slow_sliding_attacks(square, *occ, directions)).collect();
+
+        // Each slot remembers which attempt filled it, so failed attempts don't need the slots cleared
+        let mut slots: Vec<Bitboard> = vec![0; 1 << bits];
+        let mut filled_by: Vec<u32> = vec![0; 1 << bits];
+        let mut attempt: u32 = 0;
+        *magic = Magic { mask, magic: known[square], shift: 64 - bits, offset: table.len() };
+        'search: loop {
+            attempt += 1;
+            for (occ, attack) in occupancies.iter().zip(&attacks) {
+                let index = ((occ.wrapping_mul(magic.magic)) >> magic.shift) as usize;
+                if filled_by[index] != attempt {
+                    filled_by[index] = attempt;
+                    slots[index] = *attack;
+                } else if slots[index] != *attack {
+                    // Sparse numbers make good magics far more often
+                    loop {
+                        magic.magic = random() & random() & random();
+                        if (mask.wrapping_mul(magic.magic) >> 56).count_ones() >= 6 {
+                            continue 'search;
+                        }
+                    }
+                }
+            }
+            break;
+        }
+        table.extend(slots);
+    }
+    magics
+}
@@ -0,0 +1,263 @@
+use crate::bitboard::{bishop_attacks, points, queen_attacks, rook_attacks, Bitboard, KING_ATTACKS, KNIGHT_ATTACKS};
+use crate::pieces::PieceColor;
+use crate::pieces::Type;
+use crate::position::Position;
+
+// Indexed by Type::index(): pawn, knight, bishop, rook, queen, king
+pub const PIECE_VALUES: [i32; 6] = [100, 320, 330, 500, 900, 0];
+const ENDGAME_PIECE_VALUES: [i32; 6] = [120, 300, 320, 520, 940, 0];
+
+// Each piece's share of the "game phase": all of them on the board is a middlegame (24), none is an endgame (0)
+const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+const TOTAL_PHASE: i32 = 24;
+
+// Piece-square tables, seen from White's side with a8 first and h1 last (the layout they'd have printed on a diagram)
+#[rustfmt::skip]
+const PAWN_TABLE: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+     50,  50,  50,  50,  50,  50,  50,  50,
+     10,  10,  20,  30,  30,  20,  10,  10,
+      5,   5,  10,  25,  25,  10,   5,   5,
+      0,   0,   0,  20,  20,   0,   0,   0,
+      5,  -5, -10,   0,   0, -10,  -5,   5,
+      5,  10,  10, -20, -20,  10,  10,   5,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+#[rustfmt::skip]
+const ENDGAME_PAWN_TABLE: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+     80,  80,  80,  80,  80,  80,  80,  80,
+     50,  50,  50,  50,  50,  50,  50,  50,
+     30,  30,  30,  30,  30,  30,  30,  30,
+     20,  20,  20,  20,  20,  20,  20,  20,
+     10,  10,  10,  10,  10,  10,  10,  10,
+      5,   5,   5,   5,   5,   5,   5,   5,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i32; 64] = [
+    -50, -40, -30, -30, -30, -30, -40, -50,
+    -40, -20,   0,   0,   0,   0, -20, -40,
+    -30,   0,  10,  15,  15,  10,   0, -30,
+    -30,   5,  15,  20,  20,  15,   5, -30,
+    -30,   0,  15,  20,  20,  15,   0, -30,
+    -30,   5,  10,  15,  15,  10,   5, -30,
+    -40, -20,   0,   5,   5,   0, -20, -40,
+    -50, -40, -30, -30, -30, -30, -40, -50,
+];
+#[rustfmt::skip]
+const BISHOP_TABLE: [i32; 64] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   5,   5,  10,  10,   5,   5, -10,
+    -10,   0,  10,  10,  10,  10,   0, -10,
+    -10,  10,  10,  10,  10,  10,  10, -10,
+    -10,   5,   0,   0,   0,   0,   5, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20,
+];
+#[rustfmt::skip]
+const ROOK_TABLE: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+      5,  10,  10,  10,  10,  10,  10,   5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+      0,   0,   0,   5,   5,   0,   0,   0,
+];
+#[rustfmt::skip]
+const QUEEN_TABLE: [i32; 64] = [
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+     -5,   0,   5,   5,   5,   5,   0,  -5,
+      0,   0,   5,   5,   5,   5,   0,  -5,
+    -10,   5,   5,   5,   5,   5,   0, -10,
+    -10,   0,   5,   0,   0,   0,   0, -10,
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+// The King hides behind its pawns while queens are around...
+#[rustfmt::skip]
+const KING_TABLE: [i32; 64] = [
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+     20,  20,   0,   0,   0,   0,  20,  20,
+     20,  30,  10,   0,   0,  10,  30,  20,
+];
+// ...and walks to the middle once they're gone
+#[rustfmt::skip]
+const ENDGAME_KING_TABLE: [i32; 64] = [
+    -50, -40, -30, -20, -20, -30, -40, -50,
+    -30, -20, -10,   0,   0, -10, -20, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -30,   0,   0,   0,   0, -30, -30,
+    -50, -30, -30, -30, -30, -30, -30, -50,
+];
+
+// Indexed by Type::index(); only pawns and Kings change their favourite squares in the endgame
+const TABLES: [&[i32; 64]; 6] = [&PAWN_TABLE, &KNIGHT_TABLE, &BISHOP_TABLE, &ROOK_TABLE, &QUEEN_TABLE, &KING_TABLE];
+const ENDGAME_TABLES: [&[i32; 64]; 6] = [&ENDGAME_PAWN_TABLE, &KNIGHT_TABLE, &BISHOP_TABLE, &ROOK_TABLE, &QUEEN_TABLE, &ENDGAME_KING_TABLE];
+
+// Pawn structure, as (middlegame, endgame) per pawn
+const DOUBLED_PAWN: (i32, i32) = (-10, -20);
+const ISOLATED_PAWN: (i32, i32) = (-10, -15);
+// Passed pawn bonus by how many ranks the pawn has advanced from its own back rank
+const PASSED_PAWN: [(i32, i32); 8] = [(0, 0), (5, 10), (5, 15), (10, 25), (20, 45), (35, 75), (60, 120), (0, 0)];
+
+// Per attacked square a piece has beyond the number it usually has, as (middlegame, endgame)
+const MOBILITY: [(i32, i32); 6] = [(0, 0), (4, 4), (5, 5), (2, 4), (1, 2), (0, 0)];
+const USUAL_MOBILITY: [i32; 6] = [0, 4, 6, 7, 13, 0];
+
+// King safety, middlegame only: pawns sheltering the King, and pieces eyeing the squares around it
+const PAWN_SHIELD: i32 = 12;
+const KING_ZONE_ATTACK: [i32; 6] = [0, 20, 20, 40, 80, 0];
+
+const FILE: Bitboard = 0x0101_0101_0101_0101;
+
+// Static evaluation in centipawns, positive when White is better
+pub fn evaluate(position: &Position) -> i32 {
+    let (mut middlegame, mut endgame) = (0, 0);
+    let mut phase = 0;
+
+    for color in [PieceColor::White, PieceColor::Black] {
+        let sign = if color == PieceColor::White { 1 } else { -1 };
+        let (mg, eg) = evaluate_side(position, color);
+        middlegame += sign * mg;
+        endgame += sign * eg;
+
+        for piece_type in [Type::Knight, Type::Bishop, Type::Rook, Type::Queen] {
+            phase += PHASE_WEIGHTS[piece_type.index()] * position.bitboards[color.index()][piece_type.index()].count_ones() as i32;
+        }
+    }
+
+    // Blends the two scores by how much material is left (promotions can push the phase past the usual total)
+    let phase = phase.min(TOTAL_PHASE);
+    (middlegame * phase + endgame * (TOTAL_PHASE - phase)) / TOTAL_PHASE
+}
+
+// Same as evaluate, but from the point of view of the side to move (as negamax wants it)
+pub fn evaluate_relative(position: &Position) -> i32 {
+    match position.side_to_move {
+        PieceColor::White => evaluate(position),
+        PieceColor::Black => -evaluate(position),
+    }
+}
+
+// Index into the piece-square tables for a piece of the given color on the square
+fn table_index(square: usize, color: PieceColor) -> usize {
+    // Board x = 0 is the h-file and y = 0 the first rank, while the tables start at a8
+    let (x, y) = (square % 8, square / 8);
+    match color {
+        PieceColor::White => (7 - y) * 8 + (7 - x),
+        PieceColor::Black => y * 8 + (7 - x),
+    }
+}
+
+// (middlegame, endgame) score of one side's pieces, ignoring the other side's
+fn evaluate_side(position: &Position, color: PieceColor) -> (i32, i32) {
+    let ours = &position.bitboards[color.index()];
+    let their_pawns = position.bitboards[color.opposite().index()][Type::Pawn.index()];
+    let own = position.occupancy[color.index()];
+    let occupied = position.occupied();
+    let enemy_king_zone = position.king_location(color.opposite()).map_or(0, |king| KING_ATTACKS[king.index()] | (1 << king.index()));
+    let (mut mg, mut eg) = (0, 0);
+
+    for piece_type in [Type::Pawn, Type::Knight, Type::Bishop, Type::Rook, Type::Queen, Type::King] {
+        let t = piece_type.index();
+        for point in points(ours[t]) {
+            let square = point.index();
+            mg += PIECE_VALUES[t] + TABLES[t][table_index(square, color)];
+            eg += ENDGAME_PIECE_VALUES[t] + ENDGAME_TABLES[t][table_index(square, color)];
+
+            let attacks = match piece_type {
+                Type::Knight => KNIGHT_ATTACKS[square],
+                Type::Bishop => bishop_attacks(square, occupied),
+                Type::Rook => rook_attacks(square, occupied),
+                Type::Queen => queen_attacks(square, occupied),
+                _ => continue,
+            };
+            let mobility = (attacks & !own).count_ones() as i32 - USUAL_MOBILITY[t];
+            mg += mobility * MOBILITY[t].0;
+            eg += mobility * MOBILITY[t].1;
+
+            if attacks & enemy_king_zone != 0 {
+                mg += KING_ZONE_ATTACK[t];
+            }
+        }
+    }
+
+    let (pawn_mg, pawn_eg) = pawn_structure(ours[Type::Pawn.index()], their_pawns, color);
+    mg += pawn_mg + king_shelter(position, color);
+    eg += pawn_eg;
+    (mg, eg)
+}
+
+// Doubled, isolated and passed pawn terms as (middlegame, endgame)
+fn pawn_structure(pawns: Bitboard, their_pawns: Bitboard, color: PieceColor) -> (i32, i32) {
+    let (mut mg, mut eg) = (0, 0);
+
+    for x in 0..8 {
+        let on_file = (pawns & (FILE << x)).count_ones() as i32;
+        if on_file > 1 {
+            mg += (on_file - 1) * DOUBLED_PAWN.0;
+            eg += (on_file - 1) * DOUBLED_PAWN.1;
+        }
+    }
+
+    for point in points(pawns) {
+        let neighbours = adjacent_files(point.x);
+        if pawns & neighbours == 0 {
+            mg += ISOLATED_PAWN.0;
+            eg += ISOLATED_PAWN.1;
+        }
+
+        // Passed: no enemy pawn ahead of it on its own or a neighbouring file
+        let ahead = ranks_ahead(point.y, color);
+        if their_pawns & (neighbours | FILE << point.x) & ahead == 0 {
+            let advanced = point.y.abs_diff(color.home_rank()) as usize;
+            mg += PASSED_PAWN[advanced].0;
+            eg += PASSED_PAWN[advanced].1;
+        }
+    }
+    (mg, eg)
+}
+
+// Bonus for own pawns on the two ranks in front of the King, on its file and the files beside it
+fn king_shelter(position: &Position, color: PieceColor) -> i32 {
+    let king = match position.king_location(color) {
+        Some(king) => king,
+        None => return 0,
+    };
+    let files = adjacent_files(king.x) | FILE << king.x;
+    let forward = color.forward();
+    let ranks = [forward, 2 * forward]
+        .iter()
+        .filter_map(|dy| king.offset(0, *dy))
+        .fold(0, |mask: Bitboard, point| mask | 0xFF << (point.y * 8));
+
+    (position.bitboards[color.index()][Type::Pawn.index()] & files & ranks).count_ones() as i32 * PAWN_SHIELD
+}
+
+fn adjacent_files(x: u32) -> Bitboard {
+    let left = if x > 0 { FILE << (x - 1) } else { 0 };
+    let right = if x < 7 { FILE << (x + 1) } else { 0 };
+    left | right
+}
+
+// Every rank past y in the direction the color's pawns move
+fn ranks_ahead(y: u32, color: PieceColor) -> Bitboard {
+    match color {
+        PieceColor::White if y < 7 => u64::MAX << ((y + 1) * 8),
+        PieceColor::Black if y > 0 => u64::MAX >> ((8 - y) * 8),
+        _ => 0,
+    }
+}
@@ -0,0 +1,212 @@
+use crate::pieces::Piece;
+use crate::pieces::PieceColor;
+use crate::pieces::Point;
+use crate::pieces::Type;
+use crate::position::CastlingRights;
+use crate::position::Position;
+use crate::zobrist;
+
+use std::fmt;
+
+// Everything that can be wrong with a FEN string, naming the part that failed
+#[derive(Debug, PartialEq, Clone)]
+pub enum FenError {
+    // Expects 6 fields, or 4 with the move counters left out
+    FieldCount(usize),
+    RankCount(usize),
+    // Rank (1 to 8) whose pieces and empty squares don't add up to 8 squares
+    RankLength(u32),
+    InvalidPiece(char),
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+    // Each side needs exactly one King
+    KingCount(PieceColor, usize),
+    PawnOnBackRank(Point),
+    // The side that just moved can't have left its own King attacked
+    OpponentInCheck,
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::FieldCount(count) => write!(f, "expected 6 fields separated by spaces, found {count}"),
+            FenError::RankCount(count) => write!(f, "expected 8 ranks separated by '/', found {count}"),
+            FenError::RankLength(rank) => write!(f, "rank {rank} does not cover exactly 8 squares"),
+            FenError::InvalidPiece(c) => write!(f, "'{c}' is not a piece letter or empty square count"),
+            FenError::InvalidSideToMove(field) => write!(f, "side to move must be 'w' or 'b', found '{field}'"),
+            FenError::InvalidCastling(field) => write!(f, "castling rights must be '-' or a subset of 'KQkq', found '{field}'"),
+            FenError::InvalidEnPassant(field) => write!(f, "'{field}' is not a possible en passant square"),
+            FenError::InvalidHalfmoveClock(field) => write!(f, "halfmove clock must be a number, found '{field}'"),
+            FenError::InvalidFullmoveNumber(field) => write!(f, "fullmove number must be a number above 0, found '{field}'"),
+            FenError::KingCount(color, count) => write!(f, "{color:?} must have exactly one King, found {count}"),
+            FenError::PawnOnBackRank(point) => write!(f, "pawn on {} can't stand on the first or last rank", point.algebraic()),
+            FenError::OpponentInCheck => write!(f, "the side that is not to move is in check"),
+        }
+    }
+}
+
+impl Position {
+    // Reads a position in Forsyth-Edwards Notation
+    pub fn from_fen(fen: &str) -> Result<Position, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 6 && fields.len() != 4 {
+            return Err(FenError::FieldCount(fields.len()));
+        }
+
+        let mut position = Position::empty();
+
+        // Ranks are listed from the 8th down to the 1st, each from the a-file to the h-file
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenError::RankCount(ranks.len()));
+        }
+        for (i, rank) in ranks.iter().enumerate() {
+            let y = 7 - i as u32;
+            let mut file: u32 = 0;
+            for c in rank.chars() {
+                if let Some(empty) = c.to_digit(10).filter(|n| (1..=8).contains(n)) {
+                    file += empty;
+                } else {
+                    let piece_type = Type::from_letter(c).ok_or(FenError::InvalidPiece(c))?;
+                    let color = if c.is_ascii_uppercase() { PieceColor::White } else { PieceColor::Black };
+                    if file >= 8 {
+                        return Err(FenError::RankLength(y + 1));
+                    }
+                    position.put(&Point { x: 7 - file, y }, Piece { color, piece_type });
+                    file += 1;
+                }
+                if file > 8 {
+                    return Err(FenError::RankLength(y + 1));
+                }
+            }
+            if file != 8 {
+                return Err(FenError::RankLength(y + 1));
+            }
+        }
+
+        position.side_to_move = match fields[1] {
+            "w" => PieceColor::White,
+            "b" => PieceColor::Black,
+            other => return Err(FenError::InvalidSideToMove(other.to_string())),
+        };
+
+        position.castling = CastlingRights::none();
+        if fields[2] != "-" {
+            for c in fields[2].chars() {
+                let (color, king_side) = match c {
+                    'K' => (PieceColor::White, true),
+                    'Q' => (PieceColor::White, false),
+                    'k' => (PieceColor::Black, true),
+                    'q' => (PieceColor::Black, false),
+                    _ => return Err(FenError::InvalidCastling(fields[2].to_string())),
+                };
+                if position.castling.has(color, king_side) {
+                    return Err(FenError::InvalidCastling(fields[2].to_string()));
+                }
+                // A right only means something while the King and that Rook are still on their home squares
+                // (the King on the e-file, the King-side Rook at x = 0)
+                let rank = color.home_rank();
+                let king = position.piece_at(&Point { x: 3, y: rank });
+                let rook = position.piece_at(&Point { x: if king_side { 0 } else { 7 }, y: rank });
+                if king != Some(Piece { color, piece_type: Type::King }) || rook != Some(Piece { color, piece_type: Type::Rook }) {
+                    return Err(FenError::InvalidCastling(fields[2].to_string()));
+                }
+                position.castling.add(color, king_side);
+            }
+        }
+
+        // The skipped square sits behind a pawn of the side that just moved, and both it and the square
+        // the pawn started from are empty
+        if fields[3] != "-" {
+            let invalid = || FenError::InvalidEnPassant(fields[3].to_string());
+            let point = Point::from_algebraic(fields[3]).ok_or_else(invalid)?;
+            let mover = position.side_to_move.opposite();
+            let pawn = point.offset(0, mover.forward()).and_then(|p| position.piece_at(&p));
+            let start = point.offset(0, -mover.forward()).and_then(|p| position.piece_at(&p));
+            if point.y as i32 != mover.home_rank() as i32 + 2 * mover.forward()
+                || pawn != Some(Piece { color: mover, piece_type: Type::Pawn })
+                || position.piece_at(&point).is_some()
+                || start.is_some()
+            {
+                return Err(invalid());
+            }
+            position.en_passant = Some(point);
+        }
+
+        if fields.len() == 6 {
+            position.halfmove_clock = fields[4].parse().map_err(|_| FenError::InvalidHalfmoveClock(fields[4].to_string()))?;
+            position.fullmove_number = match fields[5].parse() {
+                Ok(number) if number > 0 => number,
+                _ => return Err(FenError::InvalidFullmoveNumber(fields[5].to_string())),
+            };
+        }
+
+        // Rules the rest of the game relies on: one King each, no pawns waiting on a back rank,
+        // and only the side to move can be in check
+        for color in [PieceColor::White, PieceColor::Black] {
+            let kings = position.bitboards[color.index()][Type::King.index()].count_ones() as usize;
+            if kings != 1 {
+                return Err(FenError::KingCount(color, kings));
+            }
+        }
+        if let Some((point, _)) = position.pieces().find(|(point, p)| p.piece_type == Type::Pawn && (point.y == 0 || point.y == 7)) {
+            return Err(FenError::PawnOnBackRank(point));
+        }
+        let waiting = position.side_to_move.opposite();
+        if position.is_attacked(&position.king_location(waiting).unwrap(), position.side_to_move) {
+            return Err(FenError::OpponentInCheck);
+        }
+
+        position.hash = zobrist::hash(&position);
+        Ok(position)
+    }
+
+    // Writes the position in Forsyth-Edwards Notation
+    pub fn to_fen(&self) -> String {
+        let mut ranks: Vec<String> = vec![];
+        for y in (0..8).rev() {
+            let mut rank = String::new();
+            let mut empty = 0;
+            for x in (0..8).rev() {
+                match self.piece_at(&Point { x, y }) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            rank.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        let letter = piece.piece_type.letter();
+                        rank.push(if piece.color == PieceColor::White { letter } else { letter.to_ascii_lowercase() });
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                rank.push_str(&empty.to_string());
+            }
+            ranks.push(rank);
+        }
+
+        let side = if self.side_to_move == PieceColor::White { "w" } else { "b" };
+
+        let mut castling: String = [
+            (self.castling.white_king_side, 'K'),
+            (self.castling.white_queen_side, 'Q'),
+            (self.castling.black_king_side, 'k'),
+            (self.castling.black_queen_side, 'q'),
+        ]
+        .iter()
+        .filter(|(has, _)| *has)
+        .map(|(_, c)| *c)
+        .collect();
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = self.en_passant.map_or("-".to_string(), |p| p.algebraic());
+
+        format!("{} {side} {castling} {en_passant} {} {}", ranks.join("/"), self.halfmove_clock, self.fullmove_number)
+    }
+}
@@ -0,0 +1,16 @@
+// Rules engine shared by the SDL window and the UCI engine, with no SDL code in it
+#[macro_use]
+extern crate log;
+
+pub mod bitboard;
+pub mod eval;
+pub mod fen;
+pub mod notation;
+pub mod perft;
+pub mod pgn;
+pub mod pieces;
+pub mod position;
+pub mod search;
+pub mod state;
+pub mod transposition;
+pub mod zobrist;
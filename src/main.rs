@@ -3,32 +3,45 @@ extern crate sdl2;
 #[macro_use]
 extern crate log;
 
-mod state;
+mod font;
 mod squares;
-mod pieces;
 mod renderer;
 
+use crate::renderer::{claim_button, menu_item, promotion_choices, STATUS_BAR_HEIGHT};
 use crate::renderer::Renderer;
 use crate::squares::Squares;
-use crate::state::State;
-
-use crate::pieces::PieceColor;
-use crate::pieces::Pieces;
-use crate::pieces::Type;
-use crate::pieces::Point;
+use chess::state::{claimable_draw, DrawReason, State};
+
+use chess::eval::evaluate;
+use chess::pgn::{pgn_result, save_path, Game};
+use chess::pieces::Move;
+use chess::pieces::PieceColor;
+use chess::pieces::Type;
+use chess::pieces::Point;
+use chess::position::{Position, Undo};
+use chess::search::{search, Limits};
+use chess::transposition::TranspositionTable;
 
 use log::debug;
 use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use std::time::Duration;
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::EventPump;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
 
 const SCREEN_WIDTH: u32 = 800;
 const SCREEN_HEIGHT: u32 = 800;
 
-fn get_danger_zone(pieces: &Pieces, danger_zone: &mut Vec<Point>, king_loc: &Point, index: &usize) {
-    match *pieces.types.get(*index).unwrap() {
+// How long the computer thinks about each move
+const COMPUTER_MOVETIME: Duration = Duration::from_secs(1);
+
+fn get_danger_zone(position: &Position, danger_zone: &mut Vec<Point>, king_loc: &Point, predator: &Point) {
+    match position.piece_at(predator).unwrap().piece_type {
         Type::Bishop => {
-            let bish_loc = pieces.locations.get(*index).unwrap();
+            let bish_loc = predator;
             let (mut x, mut y) = (bish_loc.x, bish_loc.y);
             // NE
             if king_loc.x > bish_loc.x && king_loc.y < bish_loc.y {
@@ -68,7 +81,7 @@ fn get_danger_zone(pieces: &Pieces, danger_zone: &mut Vec<Point>, king_loc: &Poi
         Type::Pawn => danger_zone.push(Point {x: king_loc.x, y: king_loc.y}),
 
         Type::Rook => {
-            let rook_loc = pieces.locations.get(*index).unwrap();
+            let rook_loc = predator;
             let (mut x, mut y) = (rook_loc.x, rook_loc.y);
 
             // North
@@ -97,7 +110,7 @@ fn get_danger_zone(pieces: &Pieces, danger_zone: &mut Vec<Point>, king_loc: &Poi
         },
 
         Type::Queen => {
-            let queen_loc = pieces.locations.get(*index).unwrap();
+            let queen_loc = predator;
             let (mut x, mut y) = (queen_loc.x, queen_loc.y);
 
             // Ensures King has same Y or X value for Rook moves
@@ -169,7 +182,324 @@ fn get_danger_zone(pieces: &Pieces, danger_zone: &mut Vec<Point>, king_loc: &Poi
     }
 }
 
+// Shows the promotion picker and waits until a piece is chosen
+// Returns None if the window is closed while waiting
+fn choose_promotion(events: &mut EventPump, renderer: &mut Renderer, squares: &Squares, position: &Position, point: &Point) -> Result<Option<Type>, String> {
+    let choices = promotion_choices(point);
+    let color = position.piece_at(point).unwrap().color;
+
+    renderer.render_board()?;
+    renderer.draw_pieces(squares, position)?;
+    renderer.render_promotion(squares, point, &color)?;
+
+    loop {
+        for event in events.poll_iter() {
+            match event {
+                Event::Quit { .. } | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => return Ok(None),
+                Event::MouseButtonDown { x, y, .. } => {
+                    let clicked = match squares.point_at(x, y) {
+                        Some(point) => point,
+                        None => continue,
+                    };
+                    if let Some((_, piece_type)) = choices.iter().find(|(p, _)| *p == clicked) {
+                        return Ok(Some(*piece_type));
+                    }
+                }
+                _ => {}
+            }
+        }
+        std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 30));
+    }
+}
+
+// Value given after a command line flag, e.g. `--fen "<FEN>"`
+fn argument(flag: &str) -> Result<Option<String>, String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next().map(Some).ok_or(format!("{flag} needs a value"));
+        }
+    }
+    Ok(None)
+}
+
+// Reads the starting position from `--fen "<FEN>"`, or uses the standard layout
+fn starting_position() -> Result<Position, String> {
+    match argument("--fen")? {
+        Some(fen) => {
+            debug!("LOADING FEN: {fen}");
+            Position::from_fen(&fen).map_err(|e| format!("Invalid FEN: {e}"))
+        }
+        None => Ok(Position::new()),
+    }
+}
+
+// Steps through a loaded game: Left/Right go back and forward one move, Home/End jump to the start and the end
+fn replay(events: &mut EventPump, renderer: &mut Renderer, squares: &Squares, game: &Game) -> Result<(), String> {
+    let positions = game.positions()?;
+    let white = game.tag("White").unwrap_or("?");
+    let black = game.tag("Black").unwrap_or("?");
+    let mut ply: usize = 0;
+    let mut redraw = true;
+
+    loop {
+        if redraw {
+            let last_move = match ply {
+                0 => "start".to_string(),
+                _ => game.san[ply - 1].clone(),
+            };
+            renderer.render_title(&format!("CHESS - {white} vs {black} - {ply}/{}: {last_move}", game.moves.len()))?;
+            renderer.render_board()?;
+            if ply > 0 {
+                let mv = game.moves[ply - 1];
+                renderer.render_selected(squares, &mv.from)?;
+                renderer.render_selected(squares, &mv.to)?;
+            }
+            renderer.render_pieces(squares, &positions[ply])?;
+            redraw = false;
+        }
+
+        for event in events.poll_iter() {
+            let previous = ply;
+            match event {
+                Event::Quit { .. } | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => return Ok(()),
+                Event::KeyDown { keycode: Some(Keycode::Left), .. } => ply = ply.saturating_sub(1),
+                Event::KeyDown { keycode: Some(Keycode::Right), .. } => ply = (ply + 1).min(game.moves.len()),
+                Event::KeyDown { keycode: Some(Keycode::Home), .. } => ply = 0,
+                Event::KeyDown { keycode: Some(Keycode::End), .. } => ply = game.moves.len(),
+                _ => {}
+            }
+            redraw |= ply != previous;
+        }
+        std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 30));
+    }
+}
+
+// Reads the side the computer plays from `--computer white|black`
+fn computer_side() -> Result<Option<PieceColor>, String> {
+    match argument("--computer")?.as_deref() {
+        Some("white") => Ok(Some(PieceColor::White)),
+        Some("black") => Ok(Some(PieceColor::Black)),
+        Some(other) => Err(format!("--computer must be white or black, found {other}")),
+        None => Ok(None),
+    }
+}
+
+// W and B hand White or Black to the computer, H goes back to two human players
+fn computer_key(keycode: Keycode) -> Option<PieceColor> {
+    match keycode {
+        Keycode::W => Some(PieceColor::White),
+        Keycode::B => Some(PieceColor::Black),
+        _ => None,
+    }
+}
+
+// Ctrl+Z, Backspace or Left takes a move back, Ctrl+Y or Right plays it again
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Step {
+    Back,
+    Forward,
+}
+
+fn history_step(keycode: Keycode, keymod: Mod) -> Option<Step> {
+    match keycode {
+        Keycode::Z if is_ctrl(keymod) => Some(Step::Back),
+        Keycode::Backspace | Keycode::Left => Some(Step::Back),
+        Keycode::Y if is_ctrl(keymod) => Some(Step::Forward),
+        Keycode::Right => Some(Step::Forward),
+        _ => None,
+    }
+}
+
+// Keys (and the claim button) that do the same thing whether the game is being played, in check or over. They're carried
+// out once the frame's events are handled, so every state shares the one keymap
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Hotkey {
+    // Closing the window or Escape
+    Quit,
+    // Ctrl+S
+    Save,
+    // W, B or H
+    Computer(Option<PieceColor>),
+    // E shows or hides the evaluation bar
+    Evaluation,
+    // Space or P
+    Pause,
+    // Clicking the button under the board, which only does anything while a draw can be claimed
+    ClaimDraw,
+    Step(Step),
+}
+
+fn hotkey(event: &Event) -> Option<Hotkey> {
+    match *event {
+        Event::Quit { .. } | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => Some(Hotkey::Quit),
+        Event::KeyDown { keycode: Some(Keycode::S), keymod, .. } if is_ctrl(keymod) => Some(Hotkey::Save),
+        Event::KeyDown { keycode: Some(keycode @ (Keycode::W | Keycode::B | Keycode::H)), .. } => Some(Hotkey::Computer(computer_key(keycode))),
+        Event::KeyDown { keycode: Some(Keycode::E), .. } => Some(Hotkey::Evaluation),
+        Event::KeyDown { keycode: Some(Keycode::Space | Keycode::P), .. } => Some(Hotkey::Pause),
+        Event::MouseButtonDown { x, y, .. } if claim_button().contains_point((x, y)) => Some(Hotkey::ClaimDraw),
+        Event::KeyDown { keycode: Some(keycode), keymod, .. } => history_step(keycode, keymod).map(Hotkey::Step),
+        _ => None,
+    }
+}
+
+// Choices of the menu Space or P brings up
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MenuItem {
+    Resume,
+    // Only offered while a draw can be claimed
+    ClaimDraw(DrawReason),
+    NewGame,
+    FlipBoard,
+    SaveGame,
+    Quit,
+}
+
+// Items of the pause menu, from the top
+fn pause_menu(claim: Option<DrawReason>) -> Vec<MenuItem> {
+    let mut items = vec![MenuItem::Resume];
+    items.extend(claim.map(MenuItem::ClaimDraw));
+    items.extend([MenuItem::NewGame, MenuItem::FlipBoard, MenuItem::SaveGame, MenuItem::Quit]);
+    items
+}
+
+impl MenuItem {
+    fn label(self) -> &'static str {
+        match self {
+            MenuItem::Resume => "RESUME",
+            MenuItem::ClaimDraw(_) => "CLAIM DRAW",
+            MenuItem::NewGame => "NEW GAME",
+            MenuItem::FlipBoard => "FLIP BOARD",
+            MenuItem::SaveGame => "SAVE GAME",
+            MenuItem::Quit => "QUIT",
+        }
+    }
+}
+
+// Moves played in the window, kept as the records that take them back, and the moves taken back that can be played again
+struct History {
+    takebacks: Vec<Undo>,
+    redo: Vec<Move>,
+}
+
+impl History {
+    // A new move ends the line that was taken back
+    fn push(&mut self, undo: Undo) {
+        self.takebacks.push(undo);
+        self.redo.clear();
+    }
+
+    fn undo(&mut self, position: &mut Position, game: &mut Game) -> bool {
+        match self.takebacks.pop() {
+            Some(undo) => {
+                position.unmake_move(&undo);
+                if let Some(mv) = game.take_back() {
+                    debug!("TOOK BACK {}", mv.uci());
+                    self.redo.push(mv);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn redo(&mut self, position: &mut Position, game: &mut Game) -> Result<bool, String> {
+        match self.redo.pop() {
+            Some(mv) => {
+                let before = *position;
+                self.takebacks.push(position.make_move(&mv)?);
+                game.record(&before, mv);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    // Goes back or forward a move, and on past the computer's moves so a human is the one to move next.
+    // Returns false if there was nothing to undo or redo
+    fn step(&mut self, step: Step, position: &mut Position, game: &mut Game, computer: Option<PieceColor>) -> Result<bool, String> {
+        let mut stepped = false;
+        loop {
+            let moved = match step {
+                Step::Back => self.undo(position, game),
+                Step::Forward => self.redo(position, game)?,
+            };
+            stepped |= moved;
+            if !moved || computer != Some(position.side_to_move) {
+                return Ok(stepped);
+            }
+        }
+    }
+}
+
+// Searches for the computer's move on another thread so the window keeps responding.
+// `history` is every earlier position of the game, so the computer knows which lines repeat
+fn start_search(position: Position, history: Vec<u64>, table: &Arc<Mutex<TranspositionTable>>) -> (JoinHandle<Option<Move>>, Arc<AtomicBool>) {
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_stop = Arc::clone(&stop);
+    let table = Arc::clone(table);
+    let handle = thread::spawn(move || {
+        let limits = Limits { depth: None, movetime: Some(COMPUTER_MOVETIME) };
+        let mut table = table.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        search(&position, &history, limits, &mut table, &thread_stop, |report| debug!("SEARCH: {report:?}"))
+    });
+    (handle, stop)
+}
+
+// Adds a finished move to the game, or holds on to it until the promotion picker says what the pawn became
+fn record_move(game: &mut Game, pending_move: &mut Option<(Position, Move)>, before: &Position, after: &Position, mv: Move) {
+    if after.promotion_pending().is_some() {
+        *pending_move = Some((*before, mv));
+    } else {
+        game.record(before, mv);
+    }
+}
+
+// Writes the game so far as PGN, logging instead of stopping the game if the file can't be written
+fn save_game(game: &mut Game, state: &State, path: &str) {
+    game.set_tag("Result", pgn_result(state));
+    if let Err(e) = game.save(Path::new(path)) {
+        error!("Could not save game to {path}: {e}");
+    }
+}
+
+// Stops the computer's search and throws its move away
+fn cancel_search(thinking: &mut Option<(JoinHandle<Option<Move>>, Arc<AtomicBool>)>) {
+    if let Some((handle, stop)) = thinking.take() {
+        stop.store(true, Ordering::Relaxed);
+        let _ = handle.join();
+    }
+}
+
+fn is_ctrl(keymod: Mod) -> bool {
+    keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD)
+}
+
 fn main() -> Result<(), String> {
+    // `--pgn <file>` opens the replay viewer instead of a new game
+    let loaded_game = match argument("--pgn")? {
+        Some(path) => {
+            let text = std::fs::read_to_string(&path).map_err(|e| format!("Could not read {path}: {e}"))?;
+            Some(Game::from_pgn(&text).map_err(|e| format!("Invalid PGN in {path}: {e}"))?)
+        }
+        None => None,
+    };
+    let mut position: Position = starting_position()?;
+
+    // Side played by the computer, None for two human players
+    let mut computer: Option<PieceColor> = computer_side()?;
+    let mut thinking: Option<(JoinHandle<Option<Move>>, Arc<AtomicBool>)> = None;
+    // Kept between the computer's moves, so each search starts from what the last one learned
+    let table = Arc::new(Mutex::new(TranspositionTable::default()));
+    // E shows or hides the evaluation bar
+    let mut show_evaluation = true;
+
+    // Move history, saved with Ctrl+S and when the window closes
+    let mut game = Game::new(position);
+    let mut save_file = save_path(SystemTime::now());
+    let mut pending_move: Option<(Position, Move)> = None;
+    let mut history = History { takebacks: vec![], redo: vec![] };
+
     let sdl_context = sdl2::init()?;
     let video_subsystem = sdl_context.video()?;
 
@@ -178,212 +508,380 @@ fn main() -> Result<(), String> {
     env_logger::init();
 
     // Creates Window
-    let win = video_subsystem.window("CHESS", SCREEN_WIDTH, SCREEN_HEIGHT).position_centered().build().map_err(|e| e.to_string())?;
+    let win = video_subsystem.window("CHESS", SCREEN_WIDTH, SCREEN_HEIGHT + STATUS_BAR_HEIGHT).position_centered().build().map_err(|e| e.to_string())?;
 
     // Creates Renderer struct for handling canvas renders
     let mut renderer = Renderer::new(win)?;
 
     // Creates vector for board squares
-    let squares: Squares = Squares { squares: vec![], points: vec![] }.create().unwrap();
-    let mut pieces: Pieces = Pieces { locations: vec![], colors: vec![], types: vec![], first_move: vec![] }.create().unwrap();
+    let mut squares: Squares = Squares { squares: vec![], points: vec![], flipped: false }.create().unwrap();
 
     // Creates Event Loop
     let mut events = sdl_context.event_pump()?;
 
+    if let Some(game) = loaded_game {
+        return replay(&mut events, &mut renderer, &squares, &game);
+    }
+
+    renderer.render_turn(position.side_to_move)?;
     let _ = renderer.render_board();
-    let _ = renderer.render_pieces(&squares, &pieces);
+    let _ = renderer.render_pieces(&squares, &position);
 
     // Presets variables (mutable)
     let mut first_click: bool = true;
-    let mut current_piece_loc: Option<usize> = Default::default();
     let mut valid_moves: Vec<Point> = vec![];
     let mut valid_kills: Vec<Point> = vec![];
     let mut defender_valid_moves: Vec<Point> = vec![];
     let mut defender_valid_kills: Vec<Point> = vec![];
-    let mut state: State = State::Play;
-    let mut predators_index: Vec<usize> = vec![];
     let mut current_piece = Point{y: u32::MAX, x: u32::MAX};
-    let mut prey_index: usize = usize::MAX;
+    // A loaded position may already be check or even over
+    let (mut state, mut predators): (State, Vec<Point>) = State::Play.change_state(&position)?;
+    // State to go back to when the pause menu closes, and the menu item picked out with the arrow keys
+    let mut resumed_state = state;
+    let mut menu_selection: usize = 0;
 
     // Event Loop
     'running: loop {
+        // Keys pressed this frame, carried out once the events are handled
+        let mut hotkeys: Vec<Hotkey> = vec![];
+
+        // A pawn reached the last rank on the previous move, so the game waits for the picker
+        if let Some(point) = position.promotion_pending() {
+            match choose_promotion(&mut events, &mut renderer, &squares, &position, &point)? {
+                Some(piece_type) => {
+                    position.promote(&point, piece_type)?;
+                    if let Some((before, mut mv)) = pending_move.take() {
+                        mv.promotion = Some(piece_type);
+                        game.record(&before, mv);
+                    }
+                }
+                None => break 'running,
+            }
+
+            // The promoted piece may be the one giving check. Worked out afresh, since the state from before the pick
+            // saw a pawn on the last rank and may have called a stalemate the new piece turns into mate
+            (state, predators) = State::Play.change_state(&position)?;
+            renderer.render_board()?;
+            renderer.render_pieces(&squares, &position)?;
+        }
+
+        // Kept up to date every frame. A change means the position moved on since the last redraw, so it's drawn again
+        // (Check and the end of the game redraw every frame anyway). The pause menu offers the claim from the game it paused
+        let evaluation = show_evaluation.then(|| evaluate(&position));
+        let claim = match state {
+            State::Play | State::Check => claimable_draw(&position, &game.hashes()?),
+            State::Paused => renderer.claim,
+            _ => None,
+        };
+        if (evaluation, claim) != (renderer.evaluation, renderer.claim) {
+            renderer.evaluation = evaluation;
+            renderer.claim = claim;
+            if state == State::Play {
+                renderer.render_board()?;
+                renderer.render_pieces(&squares, &position)?;
+                first_click = true;
+            }
+        }
+
+        // The computer plays its move once the search thread is done, unless it was handed back to a human meanwhile
+        if let Some((_, stop)) = &thinking {
+            if computer != Some(position.side_to_move) {
+                stop.store(true, Ordering::Relaxed);
+            }
+        }
+        if thinking.as_ref().is_some_and(|(handle, _)| handle.is_finished()) {
+            let (handle, _) = thinking.take().unwrap();
+            let best_move = handle.join().map_err(|_| "Search thread panicked")?;
+            if let Some(mv) = best_move.filter(|_| computer == Some(position.side_to_move)) {
+                let before = position;
+                history.push(position.make_move(&mv)?);
+                game.record(&before, mv);
+                debug!("COMPUTER PLAYED {}", game.san.last().unwrap());
+
+                (state, predators) = state.change_state(&position)?;
+                renderer.render_turn(position.side_to_move)?;
+                renderer.render_board()?;
+                renderer.render_pieces(&squares, &position)?;
+                first_click = true;
+            }
+        } else if thinking.is_none() && computer == Some(position.side_to_move) && matches!(state, State::Play | State::Check) {
+            let mut hashes = game.hashes()?;
+            hashes.pop();
+            thinking = Some(start_search(position, hashes, &table));
+            renderer.render_title("CHESS - Computer is thinking")?;
+        }
+
         match state {
             State::Check => {
-                 debug!("Predator(s) are {:?}", predators_index.iter().map(|x| pieces.types.get(*x).unwrap()).collect::<Vec<_>>());
+                 debug!("Predator(s) are {:?}", predators.iter().map(|p| position.piece_at(p).unwrap().piece_type).collect::<Vec<_>>());
 
                 let mut danger_zone: Vec<Point> = vec![];
-                let king_loc: Point = pieces.locations[prey_index]; 
+                let prey = position.king_location(position.side_to_move).ok_or("No King to defend")?;
+                let king_loc: Point = prey;
 
                 // Obtain the type of the predator pieces to get pathing
-                for index in &predators_index {
-                    get_danger_zone(&pieces, &mut danger_zone, &king_loc, index);
+                for predator in &predators {
+                    get_danger_zone(&position, &mut danger_zone, &king_loc, predator);
                 }
 
                 renderer.render_board()?;
                 renderer.render_danger_zones(&squares, &danger_zone); 
-                renderer.render_pieces(&squares, &pieces)?;
+                renderer.render_pieces(&squares, &position)?;
 
 
                 for event in events.poll_iter() {
-                    match event {
-                        Event::Quit { .. } | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => break 'running,
-                        Event::MouseButtonDown { x, y, .. } => {
-                            let clicked = Point {
-                                x: (x / (SCREEN_WIDTH / 8) as i32) as u32,
-                                y: (y / (SCREEN_HEIGHT / 8) as i32) as u32,
-                            };
-                            if first_click {
-                                // If only one piece puts King at risk,
-                                // another piece can block the path or kill the predator
-                                if predators_index.is_empty() {
-                                    state = State::Play;
-                                    break;
-                                }
+                    if let Some(key) = hotkey(&event) {
+                        hotkeys.push(key);
+                    } else if let Event::MouseButtonDown { x, y, .. } = event {
+                        let clicked = match squares.point_at(x, y) {
+                            Some(point) => point,
+                            None => continue,
+                        };
+                        if first_click {
+                            if predators.is_empty() {
+                                state = State::Play;
+                                break;
+                            }
 
-                                if predators_index.len() == 1 {
-                                    // 1. Find all pieces of the same color as prey
-                                    let prey_loc = pieces.locations[prey_index];
-                                    let prey_color = pieces.colors.get(prey_index).unwrap();
-
-                                    // Index of pieces of the same color as endangered King
-                                    let defense_pieces: Vec<usize> = pieces.colors.iter().enumerate().filter(|(_, x)| *x == prey_color).map(|(i, _)| i).collect();
-
-                                    // Contains index to pieces that can kill predator 
-                                    let mut defenders: Vec<usize> = vec![];
-
-                                    // 2. Check if any piece can kill predator OR can block danger
-                                    //    path.
-                                    let pred_loc: Point = pieces.locations[predators_index[0]];
-                                    for idx in defense_pieces {
-                                        let (valid_moves, valid_kills) = pieces.possible_moves(&squares, idx);
-                                        if valid_kills.iter().any(|x| *x == pred_loc) 
-                                        || valid_moves.iter().any(|p| danger_zone.contains(p) && *p != prey_loc) {
-                                            defenders.push(idx);
-                                        }
-                                    }
-                                    // 3. Ensure those that pass #3 will take King out of check
-                                    // 4. Only allow those to move.
-                                    if let Some(selected_idx) = pieces.locations.iter().position(|p| *p == clicked) {
-                                        if defenders.contains(&selected_idx) || selected_idx == prey_index {
-                                            let (def_initial_valid_moves, def_initial_valid_kills) = pieces.possible_moves(&squares, selected_idx);
-                                            defender_valid_kills = if def_initial_valid_kills.contains(&pred_loc) {vec![pred_loc]} else {vec![]};
-                                            defender_valid_moves = if clicked != prey_loc {def_initial_valid_moves.iter().filter(|p| danger_zone.contains(p) && **p != prey_loc).map(|p| *p).collect()} else {def_initial_valid_moves.iter()
-                                                .filter(|p| !danger_zone.contains(p))
-                                                .map(|p| *p)
-                                                .collect()};
-
-                                            current_piece = pieces.locations[selected_idx];
-                                            renderer.render_board()?;
-                                            renderer.render_selected(&squares, &pieces, selected_idx)?;
-                                            renderer.render_moves(&squares, &defender_valid_moves)?;
-                                            renderer.render_kills(&squares, &defender_valid_kills)?;
-                                            renderer.render_pieces(&squares, &pieces)?;
-                                            first_click = false;
-
-                                        }
-                                        
+                            // Only the endangered King's side may move, and only with moves that
+                            // take the King out of check (killing or blocking the predator, or running).
+                            // In double check no single block or kill stops both predators, so only the King can move
+                            let prey_color = position.piece_at(&prey).unwrap().color;
+                            if let Some(selected) = position.piece_at(&clicked).filter(|_| computer != Some(prey_color)) {
+                                let can_defend = if predators.len() > 1 { clicked == prey } else { selected.color == prey_color };
+                                if can_defend {
+                                    (defender_valid_moves, defender_valid_kills) = position.legal_piece_moves(&clicked);
+
+                                    if !defender_valid_moves.is_empty() || !defender_valid_kills.is_empty() {
+                                        current_piece = clicked;
+                                        renderer.render_board()?;
+                                        renderer.render_selected(&squares, &clicked)?;
+                                        renderer.render_moves(&squares, &defender_valid_moves)?;
+                                        renderer.render_kills(&squares, &defender_valid_kills)?;
+                                        renderer.render_pieces(&squares, &position)?;
+                                        first_click = false;
                                     }
                                 }
-                                // Multiple predators
-                                else {
-                                    todo!("NOT DONE YET");
-                                }
                             }
-                            else {
-                                debug!("Second click!");
-
-                                if pieces.move_piece(&defender_valid_moves, &defender_valid_kills, &current_piece, &clicked).unwrap() {
-                                    // Empties vector
-                                    predators_index = vec![];
-                                    state = State::Play;
-                                    debug!("Changed state to: {state:?}");
+                        }
+                        else {
+                            debug!("Second click!");
+
+                            let before = position;
+                            if let Some(undo) = position.move_piece(&defender_valid_moves, &defender_valid_kills, &current_piece, &clicked).unwrap() {
+                                history.push(undo);
+                                record_move(&mut game, &mut pending_move, &before, &position, Move { from: current_piece, to: clicked, promotion: None });
+                                // Empties vector
+                                (state, predators) = state.change_state(&position)?;
+                                renderer.render_turn(position.side_to_move)?;
+                                debug!("Changed state to: {state:?}");
+                                debug!("FEN: {}", position.to_fen());
 
-                                    renderer.render_board()?;
-                                    renderer.render_pieces(&squares, &pieces);
+                                renderer.render_board()?;
+                                renderer.render_pieces(&squares, &position);
 
-                                }
-                                else {
-                                    renderer.render_board()?;
-                                    renderer.render_danger_zones(&squares, &danger_zone); 
-                                    renderer.render_pieces(&squares, &pieces);
-                                }
-                                first_click = true;
                             }
+                            else {
+                                renderer.render_board()?;
+                                renderer.render_danger_zones(&squares, &danger_zone); 
+                                renderer.render_pieces(&squares, &position);
+                            }
+                            first_click = true;
                         }
-                        _ => {}
                     }
                 }
             }
             State::Play => {
                 for event in events.poll_iter() {
-                    match event {
-                        Event::Quit { .. } | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => break 'running,
-                        Event::MouseButtonDown { x, y, .. } => {
-                            let clicked = Point {
-                                x: (x / (SCREEN_WIDTH / 8) as i32) as u32,
-                                y: (y / (SCREEN_HEIGHT / 8) as i32) as u32,
-                            };
-                            if first_click {
-                                // Gets piece that's clicked on
-                                debug!("FIRST CLICK");
-                                //debug!("Coords: X: {:}, Y: {:}", clicked.x, clicked.y);
-
-                                // Ensures a piece exists at tile clicked on 
-                                current_piece_loc = pieces.locations.iter().position(|p| p.x == clicked.x && p.y == clicked.y);
-                                current_piece = clicked;
-                                let selected_type = match current_piece_loc {
-                                    Some(x) => pieces.types.get(x),
-                                    None => Option::None,
-                                };
-
-                                // Renders moves for selected piece
-                                debug!("Selected Piece: {:?}", selected_type);
-                                if selected_type.is_some() {
-                                    (valid_moves, valid_kills) = pieces.possible_moves(&squares, current_piece_loc.unwrap());
-                                    if selected_type.unwrap() == &Type::King {
-                                        let king_color = pieces.colors[current_piece_loc.unwrap()];
-                                        for (idx, clr) in pieces.colors.iter().enumerate() {
-                                            if clr != &king_color && pieces.types.get(idx).unwrap() != &Type::Pawn{
-                                                let (temp_valid_moves, _) = pieces.possible_moves(&squares, idx);
-                                                for item in temp_valid_moves {
-                                                    if let Some(pos) = valid_moves.iter().position(|x| *x == item) {
-                                                        valid_moves.remove(pos);
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
+                    if let Some(key) = hotkey(&event) {
+                        hotkeys.push(key);
+                    } else if let Event::MouseButtonDown { x, y, .. } = event {
+                        let clicked = match squares.point_at(x, y) {
+                            Some(point) => point,
+                            None => continue,
+                        };
+                        if first_click {
+                            // Gets piece that's clicked on
+                            debug!("FIRST CLICK");
+                            //debug!("Coords: X: {:}, Y: {:}", clicked.x, clicked.y);
+
+                            // Ensures a piece of the side to move exists at tile clicked on, and that a human plays that side
+                            let selected = position.piece_at(&clicked).filter(|p| p.color == position.side_to_move && computer != Some(p.color));
+                            current_piece = clicked;
+
+                            // Renders moves for selected piece
+                            debug!("Selected Piece: {:?}", selected.map(|p| p.piece_type));
+                            if selected.is_some() {
+                                (valid_moves, valid_kills) = position.legal_piece_moves(&clicked);
 
-                                    renderer.render_board()?;
-                                    renderer.render_selected(&squares, &pieces, current_piece_loc.unwrap())?;
-                                    renderer.render_moves(&squares, &valid_moves)?;
-                                    renderer.render_kills(&squares, &valid_kills)?;
-                                    renderer.render_pieces(&squares, &pieces)?;
-                                    first_click = false;
-                                }
-                            } else {
-                                debug!("SECOND CLICK");
-                                pieces.move_piece(&valid_moves, &valid_kills, &current_piece, &clicked)?;
                                 renderer.render_board()?;
-                                renderer.render_pieces(&squares, &pieces)?;
-                                first_click = true;
+                                renderer.render_selected(&squares, &clicked)?;
+                                renderer.render_moves(&squares, &valid_moves)?;
+                                renderer.render_kills(&squares, &valid_kills)?;
+                                renderer.render_pieces(&squares, &position)?;
+                                first_click = false;
+                            }
+                        } else {
+                            debug!("SECOND CLICK");
+                            let before = position;
+                            if let Some(undo) = position.move_piece(&valid_moves, &valid_kills, &current_piece, &clicked)? {
+                                history.push(undo);
+                                record_move(&mut game, &mut pending_move, &before, &position, Move { from: current_piece, to: clicked, promotion: None });
+                                (state, predators) = state.change_state(&position)?;
+                                renderer.render_turn(position.side_to_move)?;
+                                debug!("FEN: {}", position.to_fen());
+                            }
+                            renderer.render_board()?;
+                            renderer.render_pieces(&squares, &position)?;
+                            first_click = true;
+
+                            debug!("Current state: {state:?}");
+                        }
+                    }
+                }
+            }
 
-                                state = state.is_king_endangered(&squares, &mut pieces, &mut predators_index, &mut prey_index);
+            // Game is over, so moves are no longer accepted
+            State::Checkmate(_) | State::Stalemate | State::Draw(_) => {
+                let (title, subtitle) = match state {
+                    State::Checkmate(PieceColor::White) => ("CHECKMATE", "White wins"),
+                    State::Checkmate(PieceColor::Black) => ("CHECKMATE", "Black wins"),
+                    State::Draw(DrawReason::ThreefoldRepetition) => ("DRAW", "Threefold repetition"),
+                    State::Draw(DrawReason::FiftyMoveRule) => ("DRAW", "Fifty-move rule"),
+                    State::Draw(DrawReason::SeventyFiveMoveRule) => ("DRAW", "Seventy-five-move rule"),
+                    State::Draw(DrawReason::InsufficientMaterial) => ("DRAW", "Insufficient material"),
+                    _ => ("STALEMATE", "Draw"),
+                };
+                renderer.render_board()?;
+                renderer.draw_pieces(&squares, &position)?;
+                renderer.render_banner(title, subtitle)?;
 
+                for event in events.poll_iter() {
+                    hotkeys.extend(hotkey(&event));
+                }
+            }
 
-                                debug!("Current state: {state:?}");
-                            }
-                        }
+            // The menu covers the board, and nothing moves (the computer included) until it's closed
+            State::Paused => {
+                renderer.render_board()?;
+                renderer.draw_pieces(&squares, &position)?;
+                let items = pause_menu(renderer.claim);
+                let labels: Vec<&str> = items.iter().map(|item| item.label()).collect();
+                renderer.render_pause_menu(&labels, menu_selection)?;
+
+                let item_at = |x: i32, y: i32| (0..items.len()).find(|i| menu_item(*i, items.len()).contains_point((x, y)));
+                let mut chosen: Option<MenuItem> = None;
+                for event in events.poll_iter() {
+                    match event {
+                        Event::Quit { .. } => chosen = Some(MenuItem::Quit),
+                        Event::KeyDown { keycode: Some(Keycode::Space | Keycode::P | Keycode::Escape), .. } => chosen = Some(MenuItem::Resume),
+                        Event::KeyDown { keycode: Some(Keycode::Up), .. } => menu_selection = (menu_selection + items.len() - 1) % items.len(),
+                        Event::KeyDown { keycode: Some(Keycode::Down), .. } => menu_selection = (menu_selection + 1) % items.len(),
+                        Event::KeyDown { keycode: Some(Keycode::Return | Keycode::KpEnter), .. } => chosen = Some(items[menu_selection]),
+                        Event::MouseMotion { x, y, .. } => menu_selection = item_at(x, y).unwrap_or(menu_selection),
+                        Event::MouseButtonDown { x, y, .. } => chosen = item_at(x, y).map(|i| items[i]),
                         _ => {}
                     }
                 }
+
+                match chosen {
+                    Some(MenuItem::Resume) => {
+                        state = resumed_state;
+                        renderer.render_turn(position.side_to_move)?;
+                        renderer.render_board()?;
+                        renderer.render_pieces(&squares, &position)?;
+                        first_click = true;
+                    }
+                    Some(MenuItem::ClaimDraw(reason)) => {
+                        state = State::Draw(reason);
+                        debug!("DRAW CLAIMED: {state:?}");
+                    }
+                    Some(MenuItem::NewGame) => {
+                        // The finished game keeps its file, and the new one gets its own
+                        if !game.moves.is_empty() {
+                            save_game(&mut game, &resumed_state, &save_file);
+                        }
+                        position = game.start;
+                        game = Game::new(position);
+                        save_file = save_path(SystemTime::now());
+                        history = History { takebacks: vec![], redo: vec![] };
+                        pending_move = None;
+                        (state, predators) = State::Play.change_state(&position)?;
+                        renderer.render_turn(position.side_to_move)?;
+                        renderer.render_board()?;
+                        renderer.render_pieces(&squares, &position)?;
+                        first_click = true;
+                        debug!("NEW GAME");
+                    }
+                    // The menu stays open over the turned board
+                    Some(MenuItem::FlipBoard) => squares = squares.flip()?,
+                    Some(MenuItem::SaveGame) => {
+                        save_game(&mut game, &resumed_state, &save_file);
+                        renderer.render_title(&format!("CHESS - Paused - Saved to {save_file}"))?;
+                    }
+                    Some(MenuItem::Quit) => {
+                        state = resumed_state;
+                        break 'running;
+                    }
+                    None => {}
+                }
             }
+        }
 
-            State::Paused => unreachable!(),
+        for key in hotkeys {
+            match key {
+                Hotkey::Quit => break 'running,
+                Hotkey::Save => save_game(&mut game, &state, &save_file),
+                Hotkey::Computer(side) => computer = side,
+                Hotkey::Evaluation => {
+                    show_evaluation = !show_evaluation;
+                    renderer.evaluation = show_evaluation.then(|| evaluate(&position));
+                    renderer.render_board()?;
+                    renderer.render_pieces(&squares, &position)?;
+                    first_click = true;
+                }
+                // Pausing also stops the computer's clock; its search starts over once the game resumes.
+                // Whatever else was pressed waits for the menu to close
+                Hotkey::Pause => {
+                    cancel_search(&mut thinking);
+                    resumed_state = state;
+                    state = State::Paused;
+                    menu_selection = 0;
+                    renderer.render_title("CHESS - Paused")?;
+                    break;
+                }
+                Hotkey::ClaimDraw => {
+                    if let Some(reason) = renderer.claim {
+                        cancel_search(&mut thinking);
+                        state = State::Draw(reason);
+                        debug!("DRAW CLAIMED: {state:?}");
+                        break;
+                    }
+                }
+                // Any search was for the position being left, and check has to be worked out again for the new one
+                Hotkey::Step(step) => {
+                    cancel_search(&mut thinking);
+                    if history.step(step, &mut position, &mut game, computer)? {
+                        (state, predators) = State::Play.change_state(&position)?;
+                        renderer.evaluation = show_evaluation.then(|| evaluate(&position));
+                        renderer.render_turn(position.side_to_move)?;
+                        renderer.render_board()?;
+                        renderer.render_pieces(&squares, &position)?;
+                        first_click = true;
+                        debug!("FEN: {}", position.to_fen());
+                    }
+                }
+            }
         }
 
         std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 30));
     }
 
+    cancel_search(&mut thinking);
+
+    if !game.moves.is_empty() {
+        save_game(&mut game, &state, &save_file);
+    }
+
     Ok(())
 }
@@ -0,0 +1,236 @@
+use crate::pieces::Move;
+use crate::pieces::Point;
+use crate::pieces::Type;
+use crate::position::Position;
+
+use std::fmt;
+
+impl Move {
+    // Standard Algebraic Notation of the move, played from the given position (e.g. "Nbd7", "exd5", "e8=Q+", "O-O")
+    pub fn san(&self, position: &Position) -> String {
+        let piece = match position.piece_at(&self.from) {
+            Some(piece) => piece,
+            None => return String::new(),
+        };
+
+        let mut san = String::new();
+        if piece.piece_type == Type::King && self.from.x.abs_diff(self.to.x) == 2 {
+            // The King-side Rook sits on x = 0
+            san.push_str(if self.to.x < self.from.x { "O-O" } else { "O-O-O" });
+        } else {
+            let kills = self.kills(position);
+            let from = self.from.algebraic();
+
+            if piece.piece_type == Type::Pawn {
+                if kills {
+                    san.push_str(&from[..1]);
+                }
+            } else {
+                san.push(piece.piece_type.letter());
+
+                // Other pieces of the same type that could also reach the target decide how much of the origin is needed:
+                // the file if it tells them apart, otherwise the rank, otherwise both
+                let rivals: Vec<_> = position
+                    .legal_moves(piece.color)
+                    .into_iter()
+                    .filter(|(from, to)| *to == self.to && *from != self.from && position.piece_at(from) == Some(piece))
+                    .map(|(from, _)| from)
+                    .collect();
+                if !rivals.is_empty() {
+                    if rivals.iter().all(|p| p.x != self.from.x) {
+                        san.push_str(&from[..1]);
+                    } else if rivals.iter().all(|p| p.y != self.from.y) {
+                        san.push_str(&from[1..]);
+                    } else {
+                        san.push_str(&from);
+                    }
+                }
+            }
+
+            if kills {
+                san.push('x');
+            }
+            san.push_str(&self.to.algebraic());
+            if let Some(piece_type) = self.promotion {
+                san.push('=');
+                san.push(piece_type.letter());
+            }
+        }
+
+        // Check and checkmate suffixes come from the position after the move
+        let mut after = *position;
+        if after.play_move(self).is_ok() {
+            let defender = after.side_to_move;
+            if let Some(king) = after.king_location(defender) {
+                if after.is_attacked(&king, defender.opposite()) {
+                    san.push(if after.legal_moves(defender).is_empty() { '#' } else { '+' });
+                }
+            }
+        }
+        san
+    }
+
+    // Long algebraic notation used by the UCI protocol: origin, target and promotion piece (e.g. "e2e4", "e7e8q", "e1g1")
+    pub fn uci(&self) -> String {
+        let promotion = self.promotion.map_or(String::new(), |p| p.letter().to_ascii_lowercase().to_string());
+        format!("{}{}{promotion}", self.from.algebraic(), self.to.algebraic())
+    }
+
+    // Reads a move in UCI long algebraic notation, which must be one of the legal moves of the position
+    pub fn from_uci(position: &Position, uci: &str) -> Result<Move, NotationError> {
+        if !uci.is_ascii() || !(4..=5).contains(&uci.len()) {
+            return Err(NotationError::Malformed(uci.to_string()));
+        }
+        let from = Point::from_algebraic(&uci[0..2]).ok_or(NotationError::InvalidSquare(uci[0..2].to_string()))?;
+        let to = Point::from_algebraic(&uci[2..4]).ok_or(NotationError::InvalidSquare(uci[2..4].to_string()))?;
+        let promotion = match uci[4..].chars().next() {
+            Some(c) => Some(Type::from_letter(c).filter(|_| c.is_ascii_lowercase()).ok_or(NotationError::InvalidPiece(c))?),
+            None => None,
+        };
+
+        let mv = Move { from, to, promotion };
+        if position.generate_moves().contains(&mv) {
+            Ok(mv)
+        } else {
+            Err(NotationError::Illegal(uci.to_string()))
+        }
+    }
+
+    // Reads a move in Standard Algebraic Notation, matching it against the legal moves of the position.
+    // Castling may be written with zeros and "!"/"?" annotations are ignored, but a written capture,
+    // check or checkmate marker has to be true of the move
+    pub fn from_san(position: &Position, san: &str) -> Result<Move, NotationError> {
+        let annotated = san.trim_end_matches(['!', '?']);
+        let text = annotated.trim_end_matches(['+', '#']);
+        let marker = &annotated[text.len()..];
+        if text.is_empty() {
+            return Err(NotationError::Malformed(san.to_string()));
+        }
+        if marker.len() > 1 {
+            return Err(NotationError::WrongCheckMarker(san.to_string()));
+        }
+
+        let mv = if ["O-O", "0-0", "O-O-O", "0-0-0"].contains(&text) {
+            let king = position.king_location(position.side_to_move).ok_or(NotationError::Illegal(san.to_string()))?;
+            // The King-side Rook sits on x = 0, so King-side castling moves the King towards it
+            let to = if text.len() == 3 { king.offset(-2, 0) } else { king.offset(2, 0) };
+            let castle = Move { from: king, to: to.ok_or(NotationError::Illegal(san.to_string()))?, promotion: None };
+            let is_castle = position.piece_at(&king).is_some_and(|p| p.piece_type == Type::King);
+            if !is_castle || !position.generate_moves().contains(&castle) {
+                return Err(NotationError::Illegal(san.to_string()));
+            }
+            castle
+        } else {
+            let mv = parse_san_move(position, text, san)?;
+            if text.contains('x') != mv.kills(position) {
+                return Err(NotationError::WrongCapture(san.to_string()));
+            }
+            mv
+        };
+
+        // "+" or "#" must agree with what the move actually does to the other King
+        if !marker.is_empty() && !mv.san(position).ends_with(marker) {
+            return Err(NotationError::WrongCheckMarker(san.to_string()));
+        }
+        Ok(mv)
+    }
+
+    // True if the move takes a piece, including en passant onto an empty square
+    pub fn kills(&self, position: &Position) -> bool {
+        let pawn_moves_sideways = position.piece_at(&self.from).is_some_and(|p| p.piece_type == Type::Pawn) && self.from.x != self.to.x;
+        position.piece_at(&self.to).is_some() || pawn_moves_sideways
+    }
+}
+
+// Everything that can be wrong with a move written in SAN or UCI notation
+#[derive(Debug, PartialEq, Clone)]
+pub enum NotationError {
+    // Not shaped like a move at all
+    Malformed(String),
+    InvalidSquare(String),
+    InvalidPiece(char),
+    // Well formed, but no legal move of the position matches it
+    Illegal(String),
+    // More than one legal move matches and the origin doesn't tell them apart
+    Ambiguous(String),
+    // Written with "x" when nothing is taken, or without it when a piece is
+    WrongCapture(String),
+    // "+" or "#" written on a move that doesn't give check or checkmate, or the wrong one of the two
+    WrongCheckMarker(String),
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NotationError::Malformed(text) => write!(f, "'{text}' is not a move"),
+            NotationError::InvalidSquare(text) => write!(f, "'{text}' is not a square"),
+            NotationError::InvalidPiece(c) => write!(f, "'{c}' is not a piece"),
+            NotationError::Illegal(text) => write!(f, "{text} is not a legal move here"),
+            NotationError::Ambiguous(text) => write!(f, "{text} could be more than one legal move"),
+            NotationError::WrongCapture(text) => write!(f, "{text} has the capture marker wrong"),
+            NotationError::WrongCheckMarker(text) => write!(f, "{text} has the wrong check or checkmate marker"),
+        }
+    }
+}
+
+// Reads a SAN piece move or pawn move (no castling, no check marker), e.g. "Nbd7", "exd5", "e8=Q"
+fn parse_san_move(position: &Position, text: &str, san: &str) -> Result<Move, NotationError> {
+    let malformed = || NotationError::Malformed(san.to_string());
+    let mut chars: Vec<char> = text.chars().collect();
+
+    // Promotion, written "e8=Q" (or "e8Q")
+    let mut promotion: Option<Type> = None;
+    if let Some(last) = chars.last().copied().filter(|c| c.is_ascii_uppercase()) {
+        promotion = Some(Type::from_letter(last).ok_or(NotationError::InvalidPiece(last))?);
+        chars.pop();
+        if chars.last() == Some(&'=') {
+            chars.pop();
+        }
+    }
+
+    // Piece letter, defaulting to a pawn
+    let piece_type = match chars.first().copied().filter(|c| c.is_ascii_uppercase()) {
+        Some(letter) => {
+            chars.remove(0);
+            Type::from_letter(letter).ok_or(NotationError::InvalidPiece(letter))?
+        }
+        None => Type::Pawn,
+    };
+
+    if chars.len() < 2 {
+        return Err(malformed());
+    }
+    let target: String = chars.split_off(chars.len() - 2).into_iter().collect();
+    let to = Point::from_algebraic(&target).ok_or(NotationError::InvalidSquare(target))?;
+
+    // Whatever is left is the optional origin file and/or rank, then an optional "x"
+    if chars.last() == Some(&'x') {
+        chars.pop();
+    }
+    let (mut from_x, mut from_y) = (None, None);
+    for c in chars {
+        match c {
+            'a'..='h' if from_x.is_none() && from_y.is_none() => from_x = Some((b'h' - c as u8) as u32),
+            '1'..='8' if from_y.is_none() => from_y = Some(c as u32 - '1' as u32),
+            _ => return Err(malformed()),
+        }
+    }
+
+    let candidates: Vec<Move> = position
+        .generate_moves()
+        .into_iter()
+        .filter(|mv| {
+            mv.to == to
+                && mv.promotion == promotion
+                && position.piece_at(&mv.from).map(|p| p.piece_type) == Some(piece_type)
+                && from_x.is_none_or(|x| mv.from.x == x)
+                && from_y.is_none_or(|y| mv.from.y == y)
+        })
+        .collect();
+
+    match candidates.as_slice() {
+        [mv] => Ok(*mv),
+        [] => Err(NotationError::Illegal(san.to_string())),
+        _ => Err(NotationError::Ambiguous(san.to_string())),
+    }
+}
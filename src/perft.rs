@@ -0,0 +1,49 @@
+use crate::pieces::Move;
+use crate::position::Position;
+
+// Number of move sequences `depth` plies long from the position. Comparing it with published counts
+// is the standard check that move generation, en passant, castling and promotions are all right
+pub fn perft(position: &Position, depth: u32) -> u64 {
+    let mut position = *position;
+    count(&mut position, depth)
+}
+
+// Plays each move and takes it back again on the one position, so unmake_move is checked as well
+fn count(position: &mut Position, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = position.generate_moves();
+    // The last ply only needs counting, not playing
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    let mut nodes = 0;
+    for mv in moves {
+        if let Ok(undo) = position.make_move(&mv) {
+            nodes += count(position, depth - 1);
+            position.unmake_move(&undo);
+        }
+    }
+    nodes
+}
+
+// Perft split by first move, which narrows a wrong total down to the move whose subtree is off
+pub fn divide(position: &Position, depth: u32) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return vec![];
+    }
+    position
+        .generate_moves()
+        .into_iter()
+        .map(|mv| {
+            let mut child = *position;
+            let count = match child.play_move(&mv) {
+                Ok(()) => perft(&child, depth - 1),
+                Err(_) => 0,
+            };
+            (mv, count)
+        })
+        .collect()
+}
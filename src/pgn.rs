@@ -0,0 +1,279 @@
+use crate::pieces::Move;
+use crate::pieces::PieceColor;
+use crate::position::Position;
+use crate::state::State;
+
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Directory Ctrl+S and quitting write the game into
+pub const SAVE_DIR: &str = "games";
+
+// Longest movetext line, as the PGN export format asks for
+const LINE_LENGTH: usize = 79;
+
+// Every move played from the starting position, kept in both Move and SAN form
+pub struct Game {
+    // Tag pairs in the order they're written, starting with the Seven Tag Roster
+    pub tags: Vec<(String, String)>,
+    pub start: Position,
+    pub moves: Vec<Move>,
+    pub san: Vec<String>,
+}
+
+impl Game {
+    pub fn new(start: Position) -> Game {
+        let mut tags: Vec<(String, String)> = vec![
+            ("Event".to_string(), "Casual Game".to_string()),
+            ("Site".to_string(), "?".to_string()),
+            ("Date".to_string(), pgn_date(SystemTime::now())),
+            ("Round".to_string(), "-".to_string()),
+            ("White".to_string(), "?".to_string()),
+            ("Black".to_string(), "?".to_string()),
+            ("Result".to_string(), "*".to_string()),
+        ];
+
+        // Games that don't begin from the standard layout carry their starting FEN
+        let fen = start.to_fen();
+        if fen != Position::new().to_fen() {
+            tags.push(("SetUp".to_string(), "1".to_string()));
+            tags.push(("FEN".to_string(), fen));
+        }
+        Game { tags, start, moves: vec![], san: vec![] }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(key, _)| key == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    // Adds a move to the history, given the position it was played from
+    pub fn record(&mut self, before: &Position, mv: Move) {
+        let san = mv.san(before);
+        debug!("RECORDED MOVE: {san} ({})", mv.uci());
+        self.san.push(san);
+        self.moves.push(mv);
+    }
+
+    // Removes the last move from the history and returns it
+    pub fn take_back(&mut self) -> Option<Move> {
+        self.san.pop();
+        self.moves.pop()
+    }
+
+    // Writes the tags and movetext, wrapping moves onto lines of at most 79 characters
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        for (name, value) in &self.tags {
+            pgn.push_str(&format!("[{name} \"{}\"]\n", value.replace('\\', "\\\\").replace('"', "\\\"")));
+        }
+        pgn.push('\n');
+
+        let mut tokens: Vec<String> = vec![];
+        let mut number = self.start.fullmove_number;
+        let mut color = self.start.side_to_move;
+        for (i, san) in self.san.iter().enumerate() {
+            if color == PieceColor::White {
+                tokens.push(format!("{number}."));
+            } else if i == 0 {
+                // A game starting with Black's move shows the skipped White move as "..."
+                tokens.push(format!("{number}..."));
+            }
+            tokens.push(san.clone());
+
+            if color == PieceColor::Black {
+                number += 1;
+            }
+            color = color.opposite();
+        }
+        tokens.push(self.tag("Result").unwrap_or("*").to_string());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_LENGTH {
+                pgn.push_str(&line);
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        pgn.push_str(&line);
+        pgn.push('\n');
+        pgn
+    }
+
+    // Reads the first game of a PGN file: tag pairs and the main line of the movetext.
+    // Comments, NAGs and move numbers are skipped, as are variations, which never change the main line
+    pub fn from_pgn(text: &str) -> Result<Game, String> {
+        let mut tags: Vec<(String, String)> = vec![];
+        let mut sans: Vec<String> = vec![];
+        let mut chars = text.chars().peekable();
+        // Nesting level of the variation being skipped, 0 on the main line
+        let mut depth = 0;
+
+        while let Some(c) = chars.next() {
+            match c {
+                c if c.is_whitespace() => {}
+                '[' if depth == 0 && sans.is_empty() => {
+                    let tag: String = chars.by_ref().take_while(|c| *c != ']').collect();
+                    tags.push(parse_tag(&tag)?);
+                }
+                '{' => {
+                    chars.by_ref().find(|c| *c == '}');
+                }
+                ';' | '%' => {
+                    chars.by_ref().find(|c| *c == '\n');
+                }
+                '(' => depth += 1,
+                ')' => {
+                    if depth == 0 {
+                        return Err("Unmatched ')' in movetext".to_string());
+                    }
+                    depth -= 1;
+                }
+                '$' => {
+                    while chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+                        chars.next();
+                    }
+                }
+                _ => {
+                    let mut token = c.to_string();
+                    while let Some(next) = chars.peek().filter(|c| !c.is_whitespace() && !"{}()[];$".contains(**c)) {
+                        token.push(*next);
+                        chars.next();
+                    }
+                    if depth > 0 {
+                        continue;
+                    }
+                    if ["1-0", "0-1", "1/2-1/2", "*"].contains(&token.as_str()) {
+                        break;
+                    }
+
+                    // Move numbers may be glued to the move ("12.e4", "12...Nf6"). Only digits followed by a dot
+                    // count, so castling written with zeros ("0-0") stays whole
+                    let digits = token.find(|c: char| !c.is_ascii_digit()).unwrap_or(token.len());
+                    let san = match &token[digits..] {
+                        "" => "",
+                        rest if rest.starts_with('.') => rest.trim_start_matches('.'),
+                        _ => token.as_str(),
+                    };
+                    let san = san.trim_end_matches(['!', '?']);
+                    if !san.is_empty() {
+                        sans.push(san.to_string());
+                    }
+                }
+            }
+        }
+        if depth > 0 {
+            return Err("Unclosed variation in movetext".to_string());
+        }
+
+        let start = match tags.iter().find(|(name, _)| name == "FEN") {
+            Some((_, fen)) => Position::from_fen(fen).map_err(|e| format!("Invalid FEN tag: {e}"))?,
+            None => Position::new(),
+        };
+
+        let mut game = Game { tags, start, moves: vec![], san: vec![] };
+        let mut position = start;
+        for san in sans {
+            let mv = Move::from_san(&position, &san).map_err(|e| format!("Move {}: {e}", position.fullmove_number))?;
+            game.record(&position, mv);
+            position.play_move(&mv)?;
+        }
+        Ok(game)
+    }
+
+    // Position before the first move, then after each move
+    pub fn positions(&self) -> Result<Vec<Position>, String> {
+        let mut positions = vec![self.start];
+        let mut position = self.start;
+        for mv in &self.moves {
+            position.play_move(mv)?;
+            positions.push(position);
+        }
+        Ok(positions)
+    }
+
+    // Zobrist hashes of the same positions, for spotting repetitions
+    pub fn hashes(&self) -> Result<Vec<u64>, String> {
+        Ok(self.positions()?.iter().map(|position| position.hash).collect())
+    }
+
+    // Saves the game to the given file, replacing what was there
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        fs::write(path, self.to_pgn()).map_err(|e| e.to_string())?;
+        debug!("SAVED GAME TO {}", path.display());
+        Ok(())
+    }
+}
+
+// Splits the inside of a tag pair such as `Event "Casual Game"`, undoing escaped quotes and backslashes
+fn parse_tag(tag: &str) -> Result<(String, String), String> {
+    let (name, rest) = tag.trim().split_once(char::is_whitespace).ok_or(format!("Invalid tag pair: [{tag}]"))?;
+    let quoted = rest.trim();
+    if quoted.len() < 2 || !quoted.starts_with('"') || !quoted.ends_with('"') {
+        return Err(format!("Tag value must be quoted: [{tag}]"));
+    }
+
+    let mut value = String::new();
+    let mut escaped = false;
+    for c in quoted[1..quoted.len() - 1].chars() {
+        if c == '\\' && !escaped {
+            escaped = true;
+        } else {
+            value.push(c);
+            escaped = false;
+        }
+    }
+    Ok((name.to_string(), value))
+}
+
+// Result tag matching the state of the game
+pub fn pgn_result(state: &State) -> &'static str {
+    match state {
+        State::Checkmate(PieceColor::White) => "1-0",
+        State::Checkmate(PieceColor::Black) => "0-1",
+        State::Stalemate | State::Draw(_) => "1/2-1/2",
+        _ => "*",
+    }
+}
+
+// Date as "YYYY.MM.DD" in UTC
+fn pgn_date(time: SystemTime) -> String {
+    let (year, month, day) = civil_date(time);
+    format!("{year:04}.{month:02}.{day:02}")
+}
+
+// File name for a game started at the given time, e.g. "games/2024-01-31_18-05-09.pgn"
+pub fn save_path(time: SystemTime) -> String {
+    let (year, month, day) = civil_date(time);
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()) % 86_400;
+    format!("{SAVE_DIR}/{year:04}-{month:02}-{day:02}_{:02}-{:02}-{:02}.pgn", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+// Converts a time to a (year, month, day) calendar date (Howard Hinnant's days-to-civil algorithm)
+fn civil_date(time: SystemTime) -> (i64, u32, u32) {
+    let days = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -0,0 +1,502 @@
+use crate::bitboard::{bishop_attacks, bit, points, queen_attacks, rook_attacks, Bitboard, KING_ATTACKS, KNIGHT_ATTACKS, LIGHT_SQUARES, PAWN_ATTACKS};
+use crate::pieces::Move;
+use crate::pieces::Piece;
+use crate::pieces::PieceColor;
+use crate::pieces::Point;
+use crate::pieces::Type;
+use crate::zobrist;
+
+// Board layout: y = 0 is White's back rank and x = 0 is the King-side corner,
+// so the Kings start on x = 3 and the Queens on x = 4
+pub const KING_START_X: u32 = 3;
+pub const KING_SIDE_ROOK_X: u32 = 0;
+pub const QUEEN_SIDE_ROOK_X: u32 = 7;
+
+const BACK_RANK: [Type; 8] = [Type::Rook, Type::Knight, Type::Bishop, Type::King, Type::Queen, Type::Bishop, Type::Knight, Type::Rook];
+
+// Castling is allowed on a side while neither the King nor that side's Rook has moved
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CastlingRights {
+    pub white_king_side: bool,
+    pub white_queen_side: bool,
+    pub black_king_side: bool,
+    pub black_queen_side: bool,
+}
+
+impl CastlingRights {
+    pub fn all() -> CastlingRights {
+        CastlingRights { white_king_side: true, white_queen_side: true, black_king_side: true, black_queen_side: true }
+    }
+
+    pub fn none() -> CastlingRights {
+        CastlingRights { white_king_side: false, white_queen_side: false, black_king_side: false, black_queen_side: false }
+    }
+
+    pub fn has(&self, color: PieceColor, king_side: bool) -> bool {
+        match (color, king_side) {
+            (PieceColor::White, true) => self.white_king_side,
+            (PieceColor::White, false) => self.white_queen_side,
+            (PieceColor::Black, true) => self.black_king_side,
+            (PieceColor::Black, false) => self.black_queen_side,
+        }
+    }
+
+    pub fn add(&mut self, color: PieceColor, king_side: bool) {
+        match (color, king_side) {
+            (PieceColor::White, true) => self.white_king_side = true,
+            (PieceColor::White, false) => self.white_queen_side = true,
+            (PieceColor::Black, true) => self.black_king_side = true,
+            (PieceColor::Black, false) => self.black_queen_side = true,
+        }
+    }
+
+    fn remove(&mut self, color: PieceColor, king_side: bool) {
+        match (color, king_side) {
+            (PieceColor::White, true) => self.white_king_side = false,
+            (PieceColor::White, false) => self.white_queen_side = false,
+            (PieceColor::Black, true) => self.black_king_side = false,
+            (PieceColor::Black, false) => self.black_queen_side = false,
+        }
+    }
+
+    // Drops the right tied to a Rook corner once anything moves from or onto it
+    fn touch(&mut self, point: &Point) {
+        for color in [PieceColor::White, PieceColor::Black] {
+            if point.y == color.home_rank() {
+                if point.x == KING_SIDE_ROOK_X {
+                    self.remove(color, true);
+                }
+                if point.x == QUEEN_SIDE_ROOK_X {
+                    self.remove(color, false);
+                }
+            }
+        }
+    }
+}
+
+// What make_move changed that the move alone doesn't tell, so unmake_move can put the position back exactly
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Undo {
+    pub mv: Move,
+    // Piece that moved, before any promotion
+    pub piece: Piece,
+    // Piece taken and where it stood, which is beside the target for en passant
+    pub captured: Option<(Point, Piece)>,
+    pub castling: CastlingRights,
+    pub en_passant: Option<Point>,
+    pub halfmove_clock: u32,
+    pub hash: u64,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Position {
+    // Indexed by Point::index()
+    pub board: [Option<Piece>; 64],
+    // Color allowed to make the next move
+    pub side_to_move: PieceColor,
+    pub castling: CastlingRights,
+    // Square skipped over by the last double pawn push, if the previous move was one
+    pub en_passant: Option<Point>,
+    // Moves since the last capture or pawn move
+    pub halfmove_clock: u32,
+    // Starts at 1 and goes up after every Black move
+    pub fullmove_number: u32,
+    // Same pieces as the board, indexed by PieceColor::index() then Type::index(), for move generation
+    pub bitboards: [[Bitboard; 6]; 2],
+    // Every square taken by each color
+    pub occupancy: [Bitboard; 2],
+    // Zobrist hash of everything above that decides which moves are possible, kept up to date move by move
+    pub hash: u64,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Position::new()
+    }
+}
+
+impl Position {
+    // Sets up the starting layout
+    pub fn new() -> Position {
+        debug!("CREATING POSITION");
+        let mut position = Position::empty();
+        for x in 0..8 {
+            position.put(&Point { x, y: 0 }, Piece { color: PieceColor::White, piece_type: BACK_RANK[x as usize] });
+            position.put(&Point { x, y: 1 }, Piece { color: PieceColor::White, piece_type: Type::Pawn });
+            position.put(&Point { x, y: 6 }, Piece { color: PieceColor::Black, piece_type: Type::Pawn });
+            position.put(&Point { x, y: 7 }, Piece { color: PieceColor::Black, piece_type: BACK_RANK[x as usize] });
+        }
+        position.castling = CastlingRights::all();
+        position.hash = zobrist::hash(&position);
+        position
+    }
+
+    // Board with no pieces, White to move and no castling rights
+    pub fn empty() -> Position {
+        Position {
+            board: [None; 64],
+            side_to_move: PieceColor::White,
+            castling: CastlingRights::none(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            bitboards: [[0; 6]; 2],
+            occupancy: [0; 2],
+            hash: 0,
+        }
+    }
+
+    pub fn piece_at(&self, point: &Point) -> Option<Piece> {
+        self.board[point.index()]
+    }
+
+    // Places a piece on an empty point, keeping the board and bitboards in sync
+    pub fn put(&mut self, point: &Point, piece: Piece) {
+        self.board[point.index()] = Some(piece);
+        self.bitboards[piece.color.index()][piece.piece_type.index()] |= bit(point);
+        self.occupancy[piece.color.index()] |= bit(point);
+        self.hash ^= zobrist::piece_key(&piece, point);
+    }
+
+    // Removes and returns the piece on the point, keeping the board and bitboards in sync
+    pub fn take(&mut self, point: &Point) -> Option<Piece> {
+        let piece = self.board[point.index()].take()?;
+        self.bitboards[piece.color.index()][piece.piece_type.index()] &= !bit(point);
+        self.occupancy[piece.color.index()] &= !bit(point);
+        self.hash ^= zobrist::piece_key(&piece, point);
+        Some(piece)
+    }
+
+    pub fn occupied(&self) -> Bitboard {
+        self.occupancy[0] | self.occupancy[1]
+    }
+
+    // Every piece on the board with its location
+    pub fn pieces(&self) -> impl Iterator<Item = (Point, Piece)> + '_ {
+        self.board.iter().enumerate().filter_map(|(i, piece)| piece.map(|p| (Point::from_index(i), p)))
+    }
+
+    // True when neither side has enough left to ever checkmate: bare Kings, a single Bishop or Knight,
+    // or any number of Bishops that all stand on squares of one color
+    pub fn has_insufficient_material(&self) -> bool {
+        let both = |piece_type: Type| self.bitboards[0][piece_type.index()] | self.bitboards[1][piece_type.index()];
+        if both(Type::Pawn) | both(Type::Rook) | both(Type::Queen) != 0 {
+            return false;
+        }
+
+        let (knights, bishops) = (both(Type::Knight), both(Type::Bishop));
+        if knights.count_ones() + bishops.count_ones() <= 1 {
+            return true;
+        }
+        knights == 0 && (bishops & LIGHT_SQUARES == 0 || bishops & !LIGHT_SQUARES == 0)
+    }
+
+    pub fn king_location(&self, color: PieceColor) -> Option<Point> {
+        points(self.bitboards[color.index()][Type::King.index()]).next()
+    }
+
+    // Every piece of the given color attacking the inputted point
+    // (looks outwards from the point with each piece's attack pattern instead of calling possible_moves, so castling can use it)
+    pub fn attackers(&self, point: &Point, by: PieceColor) -> Bitboard {
+        let square = point.index();
+        let occupied = self.occupied();
+        let theirs = &self.bitboards[by.index()];
+
+        // A pawn of `by` attacks the point from wherever a pawn of the other color on the point would attack
+        (PAWN_ATTACKS[by.opposite().index()][square] & theirs[Type::Pawn.index()])
+            | (KNIGHT_ATTACKS[square] & theirs[Type::Knight.index()])
+            | (KING_ATTACKS[square] & theirs[Type::King.index()])
+            | (bishop_attacks(square, occupied) & (theirs[Type::Bishop.index()] | theirs[Type::Queen.index()]))
+            | (rook_attacks(square, occupied) & (theirs[Type::Rook.index()] | theirs[Type::Queen.index()]))
+    }
+
+    // Checks if any piece of the given color attacks the inputted point
+    pub fn is_attacked(&self, point: &Point, by: PieceColor) -> bool {
+        self.attackers(point, by) != 0
+    }
+
+    // Returns true if the color's King can castle on the given side
+    // (rights still held, the path is empty, and the King never stands on an attacked square)
+    fn can_castle(&self, color: PieceColor, king_side: bool) -> bool {
+        let rank = color.home_rank();
+        let rook_x = if king_side { KING_SIDE_ROOK_X } else { QUEEN_SIDE_ROOK_X };
+
+        if !self.castling.has(color, king_side)
+            || self.piece_at(&Point { x: KING_START_X, y: rank }) != Some(Piece { color, piece_type: Type::King })
+            || self.piece_at(&Point { x: rook_x, y: rank }) != Some(Piece { color, piece_type: Type::Rook })
+        {
+            return false;
+        }
+
+        // Squares between King and Rook must be empty
+        let (low, high) = if king_side { (rook_x + 1, KING_START_X) } else { (KING_START_X + 1, rook_x) };
+        if (low..high).any(|x| self.piece_at(&Point { x, y: rank }).is_some()) {
+            return false;
+        }
+
+        // King cannot castle out of, through, or into check
+        let king_path = if king_side { [KING_START_X, KING_START_X - 1, KING_START_X - 2] } else { [KING_START_X, KING_START_X + 1, KING_START_X + 2] };
+        !king_path.iter().any(|x| self.is_attacked(&Point { x: *x, y: rank }, color.opposite()))
+    }
+
+    // Moves and kills of the piece on the given point, without checking if they leave its own King attacked
+    pub fn possible_moves(&self, from: &Point) -> (Vec<Point>, Vec<Point>) {
+        let piece = match self.piece_at(from) {
+            Some(piece) => piece,
+            None => return (vec![], vec![]),
+        };
+        let color = piece.color;
+        let square = from.index();
+        let own = self.occupancy[color.index()];
+        let enemy = self.occupancy[color.opposite().index()];
+        let occupied = own | enemy;
+
+        let (moves, kills): (Bitboard, Bitboard) = match piece.piece_type {
+            Type::Pawn => {
+                let forward = color.forward();
+                let start_rank = color.home_rank() as i32 + forward;
+                let mut moves: Bitboard = 0;
+
+                // Ensures "first move" gets two possible spaces
+                if let Some(one) = from.offset(0, forward).filter(|p| occupied & bit(p) == 0) {
+                    moves |= bit(&one);
+                    if from.y as i32 == start_rank {
+                        if let Some(two) = from.offset(0, 2 * forward).filter(|p| occupied & bit(p) == 0) {
+                            moves |= bit(&two);
+                        }
+                    }
+                }
+
+                // Kills include en passant onto the empty square behind a pawn that just double-pushed
+                let en_passant = self.en_passant.map_or(0, |p| bit(&p)) & !occupied;
+                (moves, PAWN_ATTACKS[color.index()][square] & (enemy | en_passant))
+            }
+            _ => {
+                let attacks = match piece.piece_type {
+                    Type::Knight => KNIGHT_ATTACKS[square],
+                    Type::Bishop => bishop_attacks(square, occupied),
+                    Type::Rook => rook_attacks(square, occupied),
+                    Type::Queen => queen_attacks(square, occupied),
+                    _ => KING_ATTACKS[square],
+                };
+                (attacks & !occupied, attacks & enemy)
+            }
+        };
+
+        let mut possible_locations: Vec<Point> = points(moves).collect();
+        let possible_kills: Vec<Point> = points(kills).collect();
+
+        if piece.piece_type == Type::King {
+            // King-side castling (towards x = 0)
+            if self.can_castle(color, true) {
+                possible_locations.push(Point { y: from.y, x: from.x - 2 });
+            }
+
+            // Queen-side castling (towards x = 7)
+            if self.can_castle(color, false) {
+                possible_locations.push(Point { y: from.y, x: from.x + 2 });
+            }
+        }
+
+        (possible_locations, possible_kills)
+    }
+
+    // Plays the move on a copy of the board and checks the mover's King isn't left attacked
+    fn is_safe_move(&self, from: &Point, to: &Point) -> bool {
+        let color = match self.piece_at(from) {
+            Some(piece) => piece.color,
+            None => return false,
+        };
+        let mut simulated = *self;
+        simulated.play(from, to);
+
+        match simulated.king_location(color) {
+            Some(king) => !simulated.is_attacked(&king, color.opposite()),
+            None => true,
+        }
+    }
+
+    // Same as possible_moves, but without the moves that would leave the piece's own King in check
+    // (pinned pieces, King stepping into an attack, not resolving an existing check)
+    pub fn legal_piece_moves(&self, from: &Point) -> (Vec<Point>, Vec<Point>) {
+        let (moves, kills) = self.possible_moves(from);
+
+        let legal_moves = moves.into_iter().filter(|to| self.is_safe_move(from, to)).collect();
+        let legal_kills = kills.into_iter().filter(|to| self.is_safe_move(from, to)).collect();
+        (legal_moves, legal_kills)
+    }
+
+    // Every legal move (from, to) the given color can make
+    pub fn legal_moves(&self, color: PieceColor) -> Vec<(Point, Point)> {
+        let mut all_moves: Vec<(Point, Point)> = vec![];
+        for (from, _) in self.pieces().filter(|(_, p)| p.color == color) {
+            let (moves, kills) = self.legal_piece_moves(&from);
+            all_moves.extend(moves.into_iter().chain(kills).map(|to| (from, to)));
+        }
+        all_moves
+    }
+
+    // Every legal Move for the side to move, with a separate Move for each piece a pawn can promote to
+    pub fn generate_moves(&self) -> Vec<Move> {
+        let mut moves: Vec<Move> = vec![];
+        for (from, to) in self.legal_moves(self.side_to_move) {
+            let promotes = self.piece_at(&from).is_some_and(|p| p.piece_type == Type::Pawn) && to.y == self.side_to_move.opposite().home_rank();
+            if promotes {
+                moves.extend([Type::Queen, Type::Rook, Type::Bishop, Type::Knight].map(|piece_type| Move { from, to, promotion: Some(piece_type) }));
+            } else {
+                moves.push(Move { from, to, promotion: None });
+            }
+        }
+        moves
+    }
+
+    // Moves the piece without any validation, handling castling, en passant and castling rights,
+    // and returns the piece killed along with where it stood.
+    // put and take keep the hash in step with the pieces; the rest of the state is swapped out here
+    fn play(&mut self, from: &Point, to: &Point) -> Option<(Point, Piece)> {
+        self.piece_at(from)?;
+        self.hash ^= zobrist::castling_key(&self.castling) ^ zobrist::en_passant_key(self);
+        let piece = self.take(from).unwrap();
+
+        // An en passant kill takes the pawn beside the mover, not on the target
+        let killed_at = if piece.piece_type == Type::Pawn && self.en_passant == Some(*to) && self.piece_at(to).is_none() {
+            Point { x: to.x, y: from.y }
+        } else {
+            *to
+        };
+        let killed = self.take(&killed_at);
+
+        if piece.piece_type == Type::Pawn || killed.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if piece.color == PieceColor::Black {
+            self.fullmove_number += 1;
+        }
+
+        // Castling moves the King two squares, so the Rook jumps over to its other side
+        if let Some((rook_x, new_rook_x)) = castling_rook(&piece, from, to) {
+            if let Some(rook) = self.take(&Point { x: rook_x, y: to.y }) {
+                self.put(&Point { x: new_rook_x, y: to.y }, rook);
+            }
+        }
+
+        if piece.piece_type == Type::King {
+            self.castling.remove(piece.color, true);
+            self.castling.remove(piece.color, false);
+        }
+        self.castling.touch(from);
+        self.castling.touch(to);
+
+        // Remembers the skipped square of a double pawn push for the opponent's next move only
+        self.en_passant = if piece.piece_type == Type::Pawn && from.y.abs_diff(to.y) == 2 {
+            Some(Point { x: from.x, y: (from.y + to.y) / 2 })
+        } else {
+            None
+        };
+
+        self.put(to, piece);
+        self.side_to_move = self.side_to_move.opposite();
+        self.hash ^= zobrist::castling_key(&self.castling)
+            ^ zobrist::en_passant_key(self)
+            ^ zobrist::side_key(PieceColor::White)
+            ^ zobrist::side_key(PieceColor::Black);
+        killed.map(|killed| (killed_at, killed))
+    }
+
+    // current_piece = piece being moved
+    // Returns None if the clicked point isn't one of the piece's moves or kills, otherwise the record to take the move back with.
+    // A pawn reaching the last rank stays a pawn until promote is called
+    pub fn move_piece(&mut self, valid_moves: &[Point], valid_kills: &[Point], current_piece: &Point, point: &Point) -> Result<Option<Undo>, String> {
+        if self.piece_at(current_piece).is_none() {
+            return Err(format!("No piece to move at {current_piece:?}"));
+        }
+
+        // Ensures piece isn't double-clicked
+        if current_piece == point || !(valid_moves.contains(point) || valid_kills.contains(point)) {
+            return Ok(None);
+        }
+
+        if valid_kills.contains(point) {
+            debug!("KILLING PIECE");
+        } else {
+            debug!("MOVING PIECE");
+        }
+        self.make_move(&Move { from: *current_piece, to: *point, promotion: None }).map(Some)
+    }
+
+    // Plays a move already known to be legal, promoting straight away instead of waiting for the picker
+    pub fn play_move(&mut self, mv: &Move) -> Result<(), String> {
+        self.make_move(mv).map(|_| ())
+    }
+
+    // Same as play_move, but returns what unmake_move needs to take the move back
+    pub fn make_move(&mut self, mv: &Move) -> Result<Undo, String> {
+        let piece = self.piece_at(&mv.from).ok_or(format!("No piece to move at {:?}", mv.from))?;
+        // Checked up front so a bad promotion never leaves the move half played
+        if let Some(piece_type) = mv.promotion {
+            if piece.piece_type != Type::Pawn || matches!(piece_type, Type::Pawn | Type::King) {
+                return Err(format!("Cannot promote to {piece_type:?} at {:?}", mv.to));
+            }
+        }
+
+        let (castling, en_passant, halfmove_clock, hash) = (self.castling, self.en_passant, self.halfmove_clock, self.hash);
+        let captured = self.play(&mv.from, &mv.to);
+        if let Some(piece_type) = mv.promotion {
+            self.promote(&mv.to, piece_type)?;
+        }
+        Ok(Undo { mv: *mv, piece, captured, castling, en_passant, halfmove_clock, hash })
+    }
+
+    // Takes back the move make_move returned the undo record for, which must be the last one played
+    pub fn unmake_move(&mut self, undo: &Undo) {
+        let Undo { mv, piece, .. } = *undo;
+        // Takes whatever a promotion left on the target and puts the pawn back
+        self.take(&mv.to);
+        self.put(&mv.from, piece);
+        if let Some((point, captured)) = undo.captured {
+            self.put(&point, captured);
+        }
+        if let Some((rook_x, new_rook_x)) = castling_rook(&piece, &mv.from, &mv.to) {
+            if let Some(rook) = self.take(&Point { x: new_rook_x, y: mv.to.y }) {
+                self.put(&Point { x: rook_x, y: mv.to.y }, rook);
+            }
+        }
+
+        self.castling = undo.castling;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        if piece.color == PieceColor::Black {
+            self.fullmove_number -= 1;
+        }
+        self.side_to_move = piece.color;
+        self.hash = undo.hash;
+    }
+
+    // Returns the location of a pawn that has reached the last rank and is waiting to be promoted
+    pub fn promotion_pending(&self) -> Option<Point> {
+        self.pieces().find(|(point, p)| p.piece_type == Type::Pawn && point.y == p.color.opposite().home_rank()).map(|(point, _)| point)
+    }
+
+    // Replaces the pawn at the given point with the chosen piece
+    pub fn promote(&mut self, point: &Point, piece_type: Type) -> Result<(), String> {
+        match (self.piece_at(point), piece_type) {
+            (Some(pawn), Type::Queen | Type::Rook | Type::Bishop | Type::Knight) if pawn.piece_type == Type::Pawn => {
+                debug!("PROMOTING TO {piece_type:?}");
+                self.take(point);
+                self.put(point, Piece { color: pawn.color, piece_type });
+                Ok(())
+            }
+            _ => Err(format!("Cannot promote to {piece_type:?} at {point:?}")),
+        }
+    }
+}
+
+// Where the Rook starts and lands (x on the King's rank) when the King's move is castling
+fn castling_rook(piece: &Piece, from: &Point, to: &Point) -> Option<(u32, u32)> {
+    if piece.piece_type != Type::King || from.x.abs_diff(to.x) != 2 {
+        return None;
+    }
+    Some(if to.x < from.x { (KING_SIDE_ROOK_X, to.x + 1) } else { (QUEEN_SIDE_ROOK_X, to.x - 1) })
+}
@@ -1,10 +1,13 @@
-use crate::pieces::Pieces;
-use crate::pieces::Type;
-use crate::pieces::Point;
-use crate::pieces::PieceColor;
+use chess::position::Position;
+use chess::pieces::Type;
+use chess::pieces::Point;
+use chess::pieces::PieceColor;
+use chess::state::DrawReason;
 use crate::squares::Squares;
+use crate::font::{glyph, GLYPH_HEIGHT, GLYPH_WIDTH};
 
 use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
 use sdl2::rect::Rect;
 use sdl2::render::*;
 use std::path::Path;
@@ -12,15 +15,92 @@ use std::path::Path;
 const SCREEN_WIDTH: u32 = 800;
 const SCREEN_HEIGHT: u32 = 800;
 
+// Strip under the board that holds the claim button, so the button never covers a square
+pub const STATUS_BAR_HEIGHT: u32 = 48;
+
+// Pieces a pawn can be promoted to, in the order the picker shows them
+pub const PROMOTION_CHOICES: [Type; 4] = [Type::Queen, Type::Rook, Type::Bishop, Type::Knight];
+
+// Returns the sprite for the given piece
+fn sprite_path(piece_type: &Type, color: &PieceColor) -> &'static Path {
+    let name = match (piece_type, color) {
+        (Type::Pawn, PieceColor::Black) => "sprites/Pawn.bmp",
+        (Type::Pawn, PieceColor::White) => "sprites/WhitePawn.bmp",
+        (Type::Rook, PieceColor::Black) => "sprites/Rook.bmp",
+        (Type::Rook, PieceColor::White) => "sprites/WhiteRook.bmp",
+        (Type::Bishop, PieceColor::Black) => "sprites/Bishop.bmp",
+        (Type::Bishop, PieceColor::White) => "sprites/WhiteBishop.bmp",
+        (Type::Queen, PieceColor::Black) => "sprites/Queen.bmp",
+        (Type::Queen, PieceColor::White) => "sprites/WhiteQueen.bmp",
+        (Type::Knight, PieceColor::Black) => "sprites/Knight.bmp",
+        (Type::Knight, PieceColor::White) => "sprites/WhiteKnight.bmp",
+        (Type::King, PieceColor::Black) => "sprites/King.bmp",
+        (Type::King, PieceColor::White) => "sprites/WhiteKing.bmp",
+    };
+    Path::new(name)
+}
+
+// Squares covered by the promotion picker, starting on the promotion square and running towards the middle of the board
+pub fn promotion_choices(point: &Point) -> Vec<(Point, Type)> {
+    PROMOTION_CHOICES
+        .iter()
+        .enumerate()
+        .map(|(i, piece_type)| {
+            let y = if point.y == 0 { point.y + i as u32 } else { point.y - i as u32 };
+            (Point { x: point.x, y }, *piece_type)
+        })
+        .collect()
+}
+
+// Width of the evaluation bar drawn along the right edge of the board
+const EVAL_BAR_WIDTH: u32 = 12;
+
+// Button offering a claimable draw, centered in the strip under the board
+pub fn claim_button() -> Rect {
+    let (width, height) = (300, 34);
+    Rect::new((SCREEN_WIDTH - width) as i32 / 2, (SCREEN_HEIGHT + (STATUS_BAR_HEIGHT - height) / 2) as i32, width, height)
+}
+
+// Size of the pause menu's title and of each of its items
+const MENU_TITLE_SCALE: u32 = 6;
+const MENU_ITEM_WIDTH: u32 = 360;
+const MENU_ITEM_HEIGHT: u32 = 60;
+const MENU_GAP: u32 = 12;
+
+// Where the i-th of `count` pause menu items sits, stacked down the middle of the window under the title
+pub fn menu_item(i: usize, count: usize) -> Rect {
+    let title_height = GLYPH_HEIGHT * MENU_TITLE_SCALE + 40;
+    let total_height = title_height + count as u32 * (MENU_ITEM_HEIGHT + MENU_GAP);
+    let top = (SCREEN_HEIGHT - total_height) / 2 + title_height + i as u32 * (MENU_ITEM_HEIGHT + MENU_GAP);
+    Rect::new((SCREEN_WIDTH - MENU_ITEM_WIDTH) as i32 / 2, top as i32, MENU_ITEM_WIDTH, MENU_ITEM_HEIGHT)
+}
+
 pub struct Renderer {
     pub canvas: WindowCanvas,
+    // Evaluation shown in the bar, in centipawns from White's point of view (None hides the bar)
+    pub evaluation: Option<i32>,
+    // Draw the players may claim, shown as a button under the board and offered in the pause menu (None hides it)
+    pub claim: Option<DrawReason>,
 }
 
 impl Renderer {
     // Initializes renderer
     pub fn new(win: sdl2::video::Window) -> Result<Renderer, String> {
         let canvas = win.into_canvas().build().map_err(|e| e.to_string())?;
-        Ok(Renderer { canvas })
+        Ok(Renderer { canvas, evaluation: None, claim: None })
+    }
+
+    // Shows whose turn it is in the window title
+    pub fn render_turn(&mut self, side_to_move: PieceColor) -> Result<(), String> {
+        let title = match side_to_move {
+            PieceColor::White => "CHESS - White to move",
+            PieceColor::Black => "CHESS - Black to move",
+        };
+        self.render_title(title)
+    }
+
+    pub fn render_title(&mut self, title: &str) -> Result<(), String> {
+        self.canvas.window_mut().set_title(title).map_err(|e| e.to_string())
     }
 
     // Creates board tiles and renders them
@@ -40,87 +120,78 @@ impl Renderer {
                 }
             }
         }
+
+        self.canvas.set_draw_color(Color::RGB(40, 40, 40));
+        self.canvas.fill_rect(Rect::new(0, SCREEN_HEIGHT as i32, SCREEN_WIDTH, STATUS_BAR_HEIGHT))?;
         //self.canvas.present();
         Ok(())
     }
 
-    // Renders pieces onto board tiles
-    pub fn render_pieces(&mut self, squares: &Squares, pieces: &Pieces) -> Result<(), String> {
-        let texture_creator = self.canvas.texture_creator();
-        //debug!("Len of list: {:}", pieces.types.len());
-        for index in 0..pieces.types.len() {
-            let place = pieces.locations.get(index).unwrap();
-
-            match pieces.types.get(index).unwrap() {
-                Type::Pawn => {
-                    //debug!("Pawn: {:}", place.y*8+place.x);
-                    let bmp: &Path = if *pieces.colors.get(index).unwrap() == PieceColor::Black { Path::new("sprites/Pawn.bmp") } else { Path::new("sprites/WhitePawn.bmp") };
-
-                    let surface = sdl2::surface::Surface::load_bmp(bmp).unwrap();
-
-                    let texture = surface.as_texture(&texture_creator).unwrap();
-                    self.canvas.copy(&texture, None, *squares.squares.get((place.y * 8 + place.x) as usize).unwrap())?;
-                }
-                Type::Rook => {
-                    //debug!("Rook: {:}", place.y*8+place.x); .bmp
-                    let bmp: &Path = if *pieces.colors.get(index).unwrap() == PieceColor::Black { Path::new("sprites/Rook.bmp") } else { Path::new("sprites/WhiteRook.bmp") };
-
-                    let surface = sdl2::surface::Surface::load_bmp(bmp).unwrap();
-
-                    let texture = surface.as_texture(&texture_creator).unwrap();
-
-                    self.canvas.copy(&texture, None, *squares.squares.get((place.y * 8 + place.x) as usize).unwrap()).expect("COULDNT RENDER ROOK");
-                }
-                Type::Bishop => {
-                    //debug!("Bishop: {:}", place.y*8+place.x);
-                    let bmp: &Path = if *pieces.colors.get(index).unwrap() == PieceColor::Black { Path::new("sprites/Bishop.bmp") } else { Path::new("sprites/WhiteBishop.bmp") };
-
-                    let surface = sdl2::surface::Surface::load_bmp(bmp).unwrap();
-
-                    let texture = surface.as_texture(&texture_creator).unwrap();
-                    self.canvas.copy(&texture, None, *squares.squares.get((place.y * 8 + place.x) as usize).unwrap()).expect("COULDNT RENDER BISHOP");
-                }
-                Type::Queen => {
-                    //debug!("Rook: {:}", place.y*8+place.x);
-                    let bmp: &Path = if *pieces.colors.get(index).unwrap() == PieceColor::Black { Path::new("sprites/Queen.bmp") } else { Path::new("sprites/WhiteQueen.bmp") };
-
-                    let surface = sdl2::surface::Surface::load_bmp(bmp).unwrap();
-
-                    let texture = surface.as_texture(&texture_creator).unwrap();
-                    self.canvas.copy(&texture, None, *squares.squares.get((place.y * 8 + place.x) as usize).unwrap()).expect("COULDNT RENDER ROOK");
-                }
-                Type::Knight => {
-                    //debug!("Knight: {:}", place.y*8+place.x);
-                    let bmp: &Path = if *pieces.colors.get(index).unwrap() == PieceColor::Black { Path::new("sprites/Knight.bmp") } else { Path::new("sprites/WhiteKnight.bmp") };
-
-                    let surface = sdl2::surface::Surface::load_bmp(bmp).unwrap();
+    // Renders pieces onto board tiles and shows the frame
+    pub fn render_pieces(&mut self, squares: &Squares, position: &Position) -> Result<(), String> {
+        self.draw_pieces(squares, position)?;
+        self.canvas.present();
+        Ok(())
+    }
 
-                    let texture = surface.as_texture(&texture_creator).unwrap();
+    // Renders pieces onto board tiles without showing the frame, so overlays can be drawn on top
+    pub fn draw_pieces(&mut self, squares: &Squares, position: &Position) -> Result<(), String> {
+        let texture_creator = self.canvas.texture_creator();
+        for (place, piece) in position.pieces() {
+            let surface = sdl2::surface::Surface::load_bmp(sprite_path(&piece.piece_type, &piece.color))?;
+            let texture = surface.as_texture(&texture_creator).map_err(|e| e.to_string())?;
+            self.canvas.copy(&texture, None, *squares.squares.get(place.index()).unwrap())?;
+        }
+        if let Some(score) = self.evaluation {
+            self.render_eval_bar(score, squares.flipped)?;
+        }
+        if let Some(reason) = self.claim {
+            self.render_claim_button(reason)?;
+        }
+        Ok(())
+    }
 
-                    self.canvas.copy(&texture, None, *squares.squares.get((place.y * 8 + place.x) as usize).unwrap()).expect("COULDNT RENDER ROOK");
-                }
-                Type::King => {
-                    //debug!("King: {:}", place.y*8+place.x);
-                    let bmp: &Path = if *pieces.colors.get(index).unwrap() == PieceColor::Black { Path::new("sprites/King.bmp") } else { Path::new("sprites/WhiteKing.bmp") };
+    // Draws the claim button, naming the rule that allows the claim
+    pub fn render_claim_button(&mut self, reason: DrawReason) -> Result<(), String> {
+        let text = match reason {
+            DrawReason::ThreefoldRepetition => "CLAIM DRAW: REPETITION",
+            _ => "CLAIM DRAW: 50 MOVES",
+        };
+        let button = claim_button();
+        let scale = 2;
+        let width = (text.chars().count() as u32 * (GLYPH_WIDTH + 1)).saturating_sub(1) * scale;
 
-                    let surface = sdl2::surface::Surface::load_bmp(bmp).unwrap();
+        self.canvas.set_draw_color(Color::RGB(60, 60, 60));
+        self.canvas.fill_rect(button)?;
+        self.canvas.set_draw_color(Color::RGB(179, 204, 255));
+        self.canvas.fill_rect(Rect::new(button.x() + 2, button.y() + 2, button.width() - 4, button.height() - 4))?;
+        let left = button.x() + (button.width() as i32 - width as i32) / 2;
+        let top = button.y() + (button.height() as i32 - (GLYPH_HEIGHT * scale) as i32) / 2;
+        self.render_text(text, left, top, scale, Color::RGB(40, 40, 40))
+    }
 
-                    let texture = surface.as_texture(&texture_creator).unwrap();
+    // White's share of the bar grows from White's side of the board (the top unless flipped) as the evaluation favours White.
+    // The share follows the usual logistic curve, so +4 pawns already fills most of the bar
+    pub fn render_eval_bar(&mut self, score: i32, flipped: bool) -> Result<(), String> {
+        let white_share = 1.0 / (1.0 + 10f64.powf(-score as f64 / 400.0));
+        let white_height = (white_share * SCREEN_HEIGHT as f64).round() as u32;
+        let left = (SCREEN_WIDTH - EVAL_BAR_WIDTH) as i32;
+        let white_top = if flipped { (SCREEN_HEIGHT - white_height) as i32 } else { 0 };
 
-                    self.canvas.copy(&texture, None, *squares.squares.get((place.y * 8 + place.x) as usize).unwrap()).expect("COULDNT RENDER KING");
-                }
-            }
+        self.canvas.set_draw_color(Color::RGB(40, 40, 40));
+        self.canvas.fill_rect(Rect::new(left, 0, EVAL_BAR_WIDTH, SCREEN_HEIGHT))?;
+        if white_height > 0 {
+            self.canvas.set_draw_color(Color::RGB(245, 245, 245));
+            self.canvas.fill_rect(Rect::new(left, white_top, EVAL_BAR_WIDTH, white_height))?;
         }
-        self.canvas.present();
         Ok(())
     }
     
     // Highlights the selected piece's tile 
-    pub fn render_selected(&mut self, square: &Squares, pieces: &Pieces, loc: usize) -> Result<(), String> {
+    pub fn render_selected(&mut self, square: &Squares, point: &Point) -> Result<(), String> {
         debug!("RENDERING SELECTED SQUARE");
         self.canvas.set_draw_color(Color::RGB(179, 204, 255));
-        let point = pieces.locations.get(loc).expect("CANNOT FIND PIECE LOCATION");
-        let _ = self.canvas.fill_rect(*square.squares.get((point.y * 8 + point.x) as usize).unwrap());
+        let _ = self.canvas.fill_rect(*square.squares.get(point.index()).unwrap());
         Ok(())
     }
 
@@ -160,6 +231,94 @@ impl Renderer {
         Ok(())
     }
 
+    // Draws the four promotion choices over the promotion square
+    pub fn render_promotion(&mut self, squares: &Squares, point: &Point, color: &PieceColor) -> Result<(), String> {
+        debug!("RENDERING PROMOTION PICKER");
+        let texture_creator = self.canvas.texture_creator();
+
+        for (choice, piece_type) in promotion_choices(point) {
+            let rect = *squares.squares.get((choice.y * 8 + choice.x) as usize).unwrap();
+
+            // Border + background so the picker stands out from the board
+            self.canvas.set_draw_color(Color::RGB(60, 60, 60));
+            self.canvas.fill_rect(rect)?;
+            self.canvas.set_draw_color(Color::RGB(179, 204, 255));
+            self.canvas.fill_rect(Rect::new(rect.x() + 3, rect.y() + 3, rect.width() - 6, rect.height() - 6))?;
+
+            let surface = sdl2::surface::Surface::load_bmp(sprite_path(&piece_type, color))?;
+            let texture = surface.as_texture(&texture_creator).map_err(|e| e.to_string())?;
+            self.canvas.copy(&texture, None, rect)?;
+        }
+        self.canvas.present();
+        Ok(())
+    }
+
+    // Draws text with the built-in bitmap font, (x, y) being the top left corner
+    pub fn render_text(&mut self, text: &str, x: i32, y: i32, scale: u32, color: Color) -> Result<(), String> {
+        self.canvas.set_draw_color(color);
+        for (i, c) in text.chars().enumerate() {
+            let left = x + (i as u32 * (GLYPH_WIDTH + 1) * scale) as i32;
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for column in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                        self.canvas.fill_rect(Rect::new(left + (column * scale) as i32, y + (row as u32 * scale) as i32, scale, scale))?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Draws text horizontally centered on the window
+    fn render_centered_text(&mut self, text: &str, y: i32, scale: u32, color: Color) -> Result<(), String> {
+        let width = (text.chars().count() as u32 * (GLYPH_WIDTH + 1)).saturating_sub(1) * scale;
+        self.render_text(text, (SCREEN_WIDTH as i32 - width as i32) / 2, y, scale, color)
+    }
+
+    // Darkens a band across the middle of the board and writes a title and subtitle on it (e.g. the game result)
+    pub fn render_banner(&mut self, title: &str, subtitle: &str) -> Result<(), String> {
+        let band_height = GLYPH_HEIGHT * 6 + GLYPH_HEIGHT * 3 + 60;
+        let band_top = (SCREEN_HEIGHT - band_height) as i32 / 2;
+
+        self.canvas.set_blend_mode(BlendMode::Blend);
+        self.canvas.set_draw_color(Color::RGBA(20, 20, 20, 200));
+        self.canvas.fill_rect(Rect::new(0, band_top, SCREEN_WIDTH, band_height))?;
+        self.canvas.set_blend_mode(BlendMode::None);
+
+        self.render_centered_text(title, band_top + 20, 6, Color::RGB(255, 235, 153))?;
+        self.render_centered_text(subtitle, band_top + 40 + (GLYPH_HEIGHT * 6) as i32, 3, Color::RGB(230, 204, 179))?;
+        self.canvas.present();
+        Ok(())
+    }
+
+    // Darkens the whole board and lists the pause menu's items, highlighting the selected one
+    pub fn render_pause_menu(&mut self, items: &[&str], selected: usize) -> Result<(), String> {
+        self.canvas.set_blend_mode(BlendMode::Blend);
+        self.canvas.set_draw_color(Color::RGBA(20, 20, 20, 200));
+        self.canvas.fill_rect(Rect::new(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT))?;
+        self.canvas.set_blend_mode(BlendMode::None);
+
+        let first = menu_item(0, items.len());
+        let title_top = first.y() - (GLYPH_HEIGHT * MENU_TITLE_SCALE) as i32 - 40;
+        self.render_centered_text("PAUSED", title_top, MENU_TITLE_SCALE, Color::RGB(255, 235, 153))?;
+
+        let scale = 4;
+        for (i, item) in items.iter().enumerate() {
+            let rect = menu_item(i, items.len());
+            let (background, text) = if i == selected {
+                (Color::RGB(179, 204, 255), Color::RGB(40, 40, 40))
+            } else {
+                (Color::RGB(60, 60, 60), Color::RGB(230, 204, 179))
+            };
+            self.canvas.set_draw_color(background);
+            self.canvas.fill_rect(rect)?;
+            let top = rect.y() + (rect.height() as i32 - (GLYPH_HEIGHT * scale) as i32) / 2;
+            self.render_centered_text(item, top, scale, text)?;
+        }
+        self.canvas.present();
+        Ok(())
+    }
+
     // Renders all the "danger paths" as orange 
     pub fn render_danger_zones(&mut self, squares: &Squares, danger_zones: &Vec<Point>) {
         debug!("RENDERING DANGER ZONES");
@@ -0,0 +1,298 @@
+use crate::eval::{evaluate_relative, PIECE_VALUES};
+use crate::pieces::Move;
+use crate::pieces::Type;
+use crate::position::Position;
+use crate::state::FIFTY_MOVE_LIMIT;
+use crate::transposition::{Bound, TranspositionTable};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// Score of being checkmated right now; mates further away score closer to 0
+pub const MATE_SCORE: i32 = 100_000;
+pub const MAX_DEPTH: u32 = 64;
+// Deepest ply the main search goes to (quiescence may go further, without killers)
+const MAX_PLY: usize = 128;
+// Scores this close to MATE_SCORE are mates, stored in the table relative to the position instead of the root
+const MATE_BOUND: i32 = MATE_SCORE - MAX_PLY as i32;
+
+// Move ordering: best move of the last iteration or the table, then captures, then killers, then quiet moves by history
+const ORDER_BEST: i32 = 1_000_000;
+const ORDER_CAPTURE: i32 = 100_000;
+const ORDER_KILLER: i32 = 90_000;
+
+// When the search has to stop; None means no limit
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    pub depth: Option<u32>,
+    pub movetime: Option<Duration>,
+}
+
+// Result of one finished iteration, for progress output such as UCI "info" lines
+#[derive(Debug, Clone, Copy)]
+pub struct Report {
+    pub depth: u32,
+    // Centipawns from the point of view of the side to move
+    pub score: i32,
+    pub nodes: u64,
+    pub elapsed: Duration,
+    pub best_move: Move,
+}
+
+struct Search<'a> {
+    stop: &'a AtomicBool,
+    table: &'a mut TranspositionTable,
+    // Hashes of the game so far and of the line being searched, ending with the current position
+    path: Vec<u64>,
+    deadline: Option<Instant>,
+    nodes: u64,
+    aborted: bool,
+    // Two quiet moves per ply that last caused a beta cutoff there
+    killers: [[Option<Move>; 2]; MAX_PLY],
+    // How often a quiet move caused a cutoff, indexed by PieceColor::index(), then origin and target square
+    history: Box<[[[i32; 64]; 64]; 2]>,
+}
+
+// Finds the best move for the side to move with an alpha-beta search that goes one ply deeper each iteration
+// until a limit is hit or `stop` is set. `history` holds the hashes of the positions played before this one,
+// oldest first, so lines repeating them are scored as draws. Returns None only if there are no legal moves
+pub fn search(
+    position: &Position,
+    history: &[u64],
+    limits: Limits,
+    table: &mut TranspositionTable,
+    stop: &AtomicBool,
+    mut report: impl FnMut(&Report),
+) -> Option<Move> {
+    let start = Instant::now();
+    let mut root = *position;
+    let mut moves = root.generate_moves();
+    // Something legal is always returned, even if the first iteration never finishes.
+    // A move remembered from an earlier search is the best first guess
+    let remembered = table.probe(position.hash).and_then(|entry| entry.best_move).filter(|mv| moves.contains(mv));
+    let mut best_move = remembered.or(moves.first().copied())?;
+
+    table.new_search();
+    let mut path = history.to_vec();
+    path.push(position.hash);
+    let mut search = Search {
+        stop,
+        table,
+        path,
+        deadline: limits.movetime.map(|time| start + time),
+        nodes: 0,
+        aborted: false,
+        killers: [[None; 2]; MAX_PLY],
+        history: Box::new([[[0; 64]; 64]; 2]),
+    };
+    for depth in 1..=limits.depth.unwrap_or(MAX_DEPTH).min(MAX_DEPTH) {
+        search.order(&root, &mut moves, Some(best_move), 0);
+
+        let (mut alpha, beta) = (-MATE_SCORE - 1, MATE_SCORE + 1);
+        let mut iteration_best = best_move;
+        for mv in &moves {
+            let undo = root.make_move(mv).ok()?;
+            search.path.push(root.hash);
+            let score = -search.negamax(&mut root, depth - 1, 1, -beta, -alpha);
+            search.path.pop();
+            root.unmake_move(&undo);
+            if search.aborted {
+                break;
+            }
+            if score > alpha {
+                alpha = score;
+                iteration_best = *mv;
+            }
+        }
+        if search.aborted {
+            break;
+        }
+
+        best_move = iteration_best;
+        search.table.store(position.hash, depth, alpha, Bound::Exact, Some(best_move));
+        report(&Report { depth, score: alpha, nodes: search.nodes, elapsed: start.elapsed(), best_move });
+
+        // A forced mate can't get any better with more depth
+        if alpha.abs() >= MATE_SCORE - MAX_DEPTH as i32 {
+            break;
+        }
+    }
+    Some(best_move)
+}
+
+impl Search<'_> {
+    // Best score the side to move can force within `depth` plies, as long as it lies between alpha and beta
+    fn negamax(&mut self, position: &mut Position, depth: u32, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.should_stop() {
+            return 0;
+        }
+        // Heading back into an earlier position, reaching the fifty-move limit or running out of mating material
+        // lets either side hold a draw
+        if self.is_repetition(position) || position.has_insufficient_material() {
+            return 0;
+        }
+        // ...unless the move that reached the limit was checkmate, which still counts (as in State::change_state)
+        if position.halfmove_clock >= FIFTY_MOVE_LIMIT {
+            let mated = in_check(position) && position.generate_moves().is_empty();
+            return if mated { -MATE_SCORE + ply as i32 } else { 0 };
+        }
+
+        // The same position reached through another move order may already be searched deep enough
+        let entry = self.table.probe(position.hash);
+        if let Some(entry) = entry.filter(|entry| entry.depth >= depth) {
+            let score = score_from_table(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return score,
+                Bound::Lower if score >= beta => return beta,
+                Bound::Upper if score <= alpha => return alpha,
+                _ => {}
+            }
+        }
+
+        let mut moves = position.generate_moves();
+        if moves.is_empty() {
+            // Quicker mates score higher
+            return if in_check(position) { -MATE_SCORE + ply as i32 } else { 0 };
+        }
+        if depth == 0 || ply >= MAX_PLY - 1 {
+            return self.quiescence(position, ply, alpha, beta);
+        }
+
+        self.order(position, &mut moves, entry.and_then(|entry| entry.best_move), ply);
+        let original_alpha = alpha;
+        let mut best_move = None;
+        for mv in moves {
+            let undo = match position.make_move(&mv) {
+                Ok(undo) => undo,
+                Err(_) => continue,
+            };
+            self.path.push(position.hash);
+            let score = -self.negamax(position, depth - 1, ply + 1, -beta, -alpha);
+            self.path.pop();
+            position.unmake_move(&undo);
+            if self.aborted {
+                return 0;
+            }
+
+            if score >= beta {
+                // Quiet moves good enough to refute this line are tried early in sibling positions too
+                if !mv.kills(position) {
+                    if self.killers[ply][0] != Some(mv) {
+                        self.killers[ply][1] = self.killers[ply][0];
+                        self.killers[ply][0] = Some(mv);
+                    }
+                    self.history[position.side_to_move.index()][mv.from.index()][mv.to.index()] += (depth * depth) as i32;
+                }
+                self.table.store(position.hash, depth, score_to_table(beta, ply), Bound::Lower, Some(mv));
+                return beta;
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = Some(mv);
+            }
+        }
+
+        let bound = if alpha > original_alpha { Bound::Exact } else { Bound::Upper };
+        self.table.store(position.hash, depth, score_to_table(alpha, ply), bound, best_move);
+        alpha
+    }
+
+    // Keeps searching captures and promotions past the depth limit, so positions aren't scored halfway through
+    // an exchange. The side to move can always "stand pat" instead of taking
+    fn quiescence(&mut self, position: &mut Position, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.should_stop() {
+            return 0;
+        }
+
+        let stand_pat = evaluate_relative(position);
+        if stand_pat >= beta {
+            return beta;
+        }
+        alpha = alpha.max(stand_pat);
+
+        let mut moves: Vec<Move> = position.generate_moves().into_iter().filter(|mv| mv.kills(position) || mv.promotion.is_some()).collect();
+        self.order(position, &mut moves, None, ply);
+        for mv in moves {
+            let undo = match position.make_move(&mv) {
+                Ok(undo) => undo,
+                Err(_) => continue,
+            };
+            let score = -self.quiescence(position, ply + 1, -beta, -alpha);
+            position.unmake_move(&undo);
+            if self.aborted {
+                return 0;
+            }
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+
+    // Sorts the moves so the likeliest best ones come first, which lets alpha-beta cut off sooner
+    fn order(&self, position: &Position, moves: &mut [Move], best: Option<Move>, ply: usize) {
+        let color = position.side_to_move.index();
+        moves.sort_by_cached_key(|mv| {
+            let score = if Some(*mv) == best {
+                ORDER_BEST
+            } else if mv.kills(position) {
+                // Most valuable victim, least valuable attacker (en passant lands on an empty square and takes a pawn)
+                let victim = position.piece_at(&mv.to).map_or(Type::Pawn, |p| p.piece_type);
+                let attacker = position.piece_at(&mv.from).map_or(Type::Pawn, |p| p.piece_type);
+                ORDER_CAPTURE + PIECE_VALUES[victim.index()] * 10 - PIECE_VALUES[attacker.index()] / 10
+            } else if mv.promotion.is_some() {
+                ORDER_CAPTURE + mv.promotion.map_or(0, |p| PIECE_VALUES[p.index()])
+            } else if self.killers.get(ply).is_some_and(|killers| killers.contains(&Some(*mv))) {
+                ORDER_KILLER
+            } else {
+                self.history[color][mv.from.index()][mv.to.index()].min(ORDER_KILLER - 1)
+            };
+            -score
+        });
+    }
+
+    // Only positions since the last capture or pawn move can come back, and only every other ply,
+    // when the same side is to move again
+    fn is_repetition(&self, position: &Position) -> bool {
+        self.path.iter().rev().take(position.halfmove_clock as usize + 1).skip(2).step_by(2).any(|hash| *hash == position.hash)
+    }
+
+    // Checks the stop flag and the clock, the clock only every few thousand nodes
+    fn should_stop(&mut self) -> bool {
+        if !self.aborted {
+            let out_of_time = self.nodes.is_multiple_of(2048) && self.deadline.is_some_and(|deadline| Instant::now() >= deadline);
+            self.aborted = out_of_time || self.stop.load(Ordering::Relaxed);
+        }
+        self.aborted
+    }
+}
+
+fn in_check(position: &Position) -> bool {
+    let color = position.side_to_move;
+    position.king_location(color).is_some_and(|king| position.is_attacked(&king, color.opposite()))
+}
+
+// Mate scores count plies from the root, but the table is shared by every path to a position,
+// so they're stored counting from the position itself
+fn score_to_table(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score + ply as i32
+    } else if score <= -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_table(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score - ply as i32
+    } else if score <= -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
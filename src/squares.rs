@@ -1,9 +1,12 @@
 use sdl2::rect::Rect;
-use crate::pieces::Point;
+use chess::pieces::Point;
 
 pub struct Squares {
+    // Screen area of each point, indexed by Point::index()
     pub squares: Vec<Rect>,
     pub points: Vec<Point>,
+    // Black's back rank at the top instead of White's
+    pub flipped: bool,
 }
 
 const SCREEN_WIDTH: u32 = 800;
@@ -14,7 +17,9 @@ impl Squares {
         let width: u32 = SCREEN_WIDTH / 8;
         let height: u32 = SCREEN_HEIGHT / 8;
         for index in 0..64 {
-            self.squares.push(Rect::new((width * (index % 8)) as i32, (height * (index / 8)) as i32, width, height));
+            // Flipping turns the board half a circle, so both x and y run the other way
+            let (column, row) = if self.flipped { (7 - index % 8, 7 - index / 8) } else { (index % 8, index / 8) };
+            self.squares.push(Rect::new((width * column) as i32, (height * row) as i32, width, height));
             self.points.push(Point {
                 x: (width * (index % 8)) / (SCREEN_WIDTH / 8),
                 y: (height * (index / 8)) / (SCREEN_HEIGHT / 8),
@@ -22,4 +27,14 @@ impl Squares {
         }
         Ok(self)
     }
+
+    // Turns the board around, keeping each point tied to its new place on the screen
+    pub fn flip(self) -> Result<Self, String> {
+        Squares { squares: vec![], points: vec![], flipped: !self.flipped }.create()
+    }
+
+    // Point under the given window coordinates
+    pub fn point_at(&self, x: i32, y: i32) -> Option<Point> {
+        self.squares.iter().position(|rect| rect.contains_point((x, y))).map(|i| self.points[i])
+    }
 }
@@ -1,56 +1,84 @@
-use crate::squares::Squares;
-
-use crate::pieces::Pieces;
-use crate::pieces::Type;
+use crate::bitboard::points;
 use crate::pieces::PieceColor;
+use crate::pieces::Point;
+use crate::position::Position;
+
+// Plies without a capture or pawn move after which a draw can be claimed, and after which the game is drawn outright
+pub const FIFTY_MOVE_LIMIT: u32 = 100;
+pub const SEVENTY_FIVE_MOVE_LIMIT: u32 = 150;
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum State {
     Paused,
     Play,
     Check,
+    // Holds the color of the winning side
+    Checkmate(PieceColor),
+    Stalemate,
+    Draw(DrawReason),
+}
+
+// Ways a game is drawn other than stalemate
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum DrawReason {
+    // Claimed when the same position comes up for the third time
+    ThreefoldRepetition,
+    // Claimed after fifty moves each without a capture or pawn move
+    FiftyMoveRule,
+    // Seventy-five moves each without a capture or pawn move end the game without a claim
+    SeventyFiveMoveRule,
+    // Neither side has the pieces left to checkmate
+    InsufficientMaterial,
 }
 
 impl State {
+    // State of the game once the position is reached, along with every piece giving check.
+    // Only reads the position, so the window just shows whatever comes back. A finished game stays finished;
+    // call it on State::Play to work the state out afresh (e.g. after a takeback)
+    pub fn change_state(self, position: &Position) -> Result<(State, Vec<Point>), String> {
+        if matches!(self, State::Checkmate(_) | State::Stalemate | State::Draw(_)) {
+            return Ok((self, vec![]));
+        }
 
-    // Changes state to Check if King is at risk, and returns King's index
-    pub(crate) fn is_king_endangered(&mut self, squares: &Squares, pieces: &mut Pieces, pred_index: &mut Vec<usize>, prey_index: &mut usize) -> State {
-        let num_of_pieces: usize = pieces.locations.len();
-
-        let mut temp = State::Play;
-
-        let black_king_index = pieces.types.iter()
-            .enumerate()
-            .position(|(i, t)| *t == Type::King && *pieces.colors.get(i).unwrap() == PieceColor::Black).unwrap();
-
-        let white_king_index = pieces.types.iter()
-            .enumerate()
-            .position(|(i, t)| *t == Type::King && *pieces.colors.get(i).unwrap() == PieceColor::White).unwrap();
-
-        // Checks if each piece has a King in its kill path
-        for index in 0..num_of_pieces {
-            let (_, valid_kills) = pieces.possible_moves(&squares, index);
-            for pnt in valid_kills {
-                if &pnt == pieces.locations.get(black_king_index).unwrap() {
-                    debug!("Black King in DANGER!");
-                    pred_index.push(index);
-                    *prey_index = black_king_index;
-                    return State::Check;
-                }
-
-                if &pnt == pieces.locations.get(white_king_index).unwrap() {
-                    debug!("White King in DANGER!");
-                    pred_index.push(index);
-                    *prey_index = white_king_index;
-                    return State::Check;
-                }
-            }
+        // Only the side to move can be in check, since the other side just made a legal move
+        let to_move = position.side_to_move;
+        let king = position.king_location(to_move).ok_or(format!("{to_move:?} has no King"))?;
+        // Two attackers means double check
+        let predators: Vec<Point> = points(position.attackers(&king, to_move.opposite())).collect();
+
+        // No legal moves: checkmate if the King is attacked, stalemate otherwise
+        let state = if position.legal_moves(to_move).is_empty() {
+            if predators.is_empty() { State::Stalemate } else { State::Checkmate(to_move.opposite()) }
+        } else if position.halfmove_clock >= SEVENTY_FIVE_MOVE_LIMIT {
+            // Checkmate on the last allowed move still counts, which is why this comes second
+            State::Draw(DrawReason::SeventyFiveMoveRule)
+        } else if position.has_insufficient_material() {
+            // Captures are what strip the material away, but a loaded position may already be dead
+            State::Draw(DrawReason::InsufficientMaterial)
+        } else if !predators.is_empty() {
+            debug!("{to_move:?} King in DANGER from {} piece(s)!", predators.len());
+            State::Check
+        } else {
+            State::Play
+        };
+
+        if !matches!(state, State::Play | State::Check) {
+            debug!("Game over: {state:?}");
         }
-        return State::Play;
-    }
-    pub(crate) fn change_state(self, squares: &Squares, pieces: &mut Pieces) -> Result<(State, Vec<usize>), String> {
-        todo!()
+        Ok((state, predators))
     }
 }
 
-
+// Draw the players may claim in the current position. `hashes` holds the hash of every position
+// of the game so far, ending with the current one
+pub fn claimable_draw(position: &Position, hashes: &[u64]) -> Option<DrawReason> {
+    // Only positions since the last capture or pawn move can come back
+    let repetitions = hashes.iter().rev().take(position.halfmove_clock as usize + 1).filter(|hash| **hash == position.hash).count();
+    if repetitions >= 3 {
+        Some(DrawReason::ThreefoldRepetition)
+    } else if position.halfmove_clock >= FIFTY_MOVE_LIMIT {
+        Some(DrawReason::FiftyMoveRule)
+    } else {
+        None
+    }
+}
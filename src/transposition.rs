@@ -0,0 +1,82 @@
+use crate::pieces::Move;
+
+use std::mem::size_of;
+
+// Table size used when nothing else is asked for
+pub const DEFAULT_MEGABYTES: usize = 16;
+
+// How a stored score relates to the real one: exact, or only known to be at least/at most that much
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Entry {
+    // Full hash, since many positions share a slot
+    pub key: u64,
+    pub depth: u32,
+    pub score: i32,
+    pub bound: Bound,
+    pub best_move: Option<Move>,
+    // Search the entry was written by, so leftovers from earlier moves are replaced first
+    generation: u8,
+}
+
+// Results of positions already searched, looked up by Zobrist hash. The table never grows:
+// each hash maps to a single slot, and a new result only evicts the old one if it searched at least
+// as deep or the old one is from an earlier search
+pub struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+    generation: u8,
+}
+
+impl TranspositionTable {
+    // Rounds down to a power of two entries so the slot is just the low bits of the hash
+    pub fn new(megabytes: usize) -> TranspositionTable {
+        let count = (megabytes.max(1) * 1024 * 1024 / size_of::<Option<Entry>>()).max(1);
+        TranspositionTable { entries: vec![None; 1 << count.ilog2()], generation: 0 }
+    }
+
+    fn slot(&self, key: u64) -> usize {
+        key as usize & (self.entries.len() - 1)
+    }
+
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        self.entries[self.slot(key)].filter(|entry| entry.key == key)
+    }
+
+    pub fn store(&mut self, key: u64, depth: u32, score: i32, bound: Bound, best_move: Option<Move>) {
+        let generation = self.generation;
+        let slot = self.slot(key);
+        let replace = match &self.entries[slot] {
+            None => true,
+            Some(old) => old.key == key || old.generation != generation || depth >= old.depth,
+        };
+        if !replace {
+            return;
+        }
+
+        // A fail-low finds no best move, but the one from an earlier visit is still worth trying first
+        let best_move = best_move.or(self.entries[slot].filter(|old| old.key == key).and_then(|old| old.best_move));
+        self.entries[slot] = Some(Entry { key, depth, score, bound, best_move, generation });
+    }
+
+    // Called at the start of every search, making the entries written so far the first to be replaced
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+        self.generation = 0;
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new(DEFAULT_MEGABYTES)
+    }
+}
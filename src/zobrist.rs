@@ -0,0 +1,68 @@
+use crate::bitboard::PAWN_ATTACKS;
+use crate::pieces::{Piece, PieceColor, Point, Type};
+use crate::position::{CastlingRights, Position};
+
+// Random keys XORed together to fingerprint a position. Built at compile time from a fixed seed,
+// so the same position hashes the same way in every run
+const KEYS: [u64; 2 * 6 * 64 + 4 + 8 + 1] = random_keys();
+
+const CASTLING_OFFSET: usize = 2 * 6 * 64;
+const EN_PASSANT_OFFSET: usize = CASTLING_OFFSET + 4;
+const SIDE_OFFSET: usize = EN_PASSANT_OFFSET + 8;
+
+// splitmix64, which is simple enough to run in a const fn
+const fn random_keys<const N: usize>() -> [u64; N] {
+    let mut keys = [0; N];
+    let mut state: u64 = 0x2D35_8DCC_AA6C_78A5;
+    let mut i = 0;
+    while i < N {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+}
+
+pub fn piece_key(piece: &Piece, point: &Point) -> u64 {
+    KEYS[(piece.color.index() * 6 + piece.piece_type.index()) * 64 + point.index()]
+}
+
+// One key per right still held
+pub fn castling_key(castling: &CastlingRights) -> u64 {
+    [castling.white_king_side, castling.white_queen_side, castling.black_king_side, castling.black_queen_side]
+        .iter()
+        .enumerate()
+        .filter(|(_, has)| **has)
+        .fold(0, |key, (i, _)| key ^ KEYS[CASTLING_OFFSET + i])
+}
+
+// The en passant square only counts while a pawn of the side to move could actually take there,
+// so the same position reached with or without a double push still hashes the same
+pub fn en_passant_key(position: &Position) -> u64 {
+    let point = match position.en_passant {
+        Some(point) => point,
+        None => return 0,
+    };
+    let color = position.side_to_move;
+    let capturers = PAWN_ATTACKS[color.opposite().index()][point.index()] & position.bitboards[color.index()][Type::Pawn.index()];
+    if capturers == 0 {
+        return 0;
+    }
+    KEYS[EN_PASSANT_OFFSET + point.x as usize]
+}
+
+pub fn side_key(color: PieceColor) -> u64 {
+    match color {
+        PieceColor::White => 0,
+        PieceColor::Black => KEYS[SIDE_OFFSET],
+    }
+}
+
+// Hash of the position built from scratch, which the incremental updates in Position::play must always match
+pub fn hash(position: &Position) -> u64 {
+    let pieces = position.pieces().fold(0, |key, (point, piece)| key ^ piece_key(&piece, &point));
+    pieces ^ castling_key(&position.castling) ^ en_passant_key(position) ^ side_key(position.side_to_move)
+}
@@ -0,0 +1,40 @@
+// Setting up positions and games for the tests. Each test file uses only some of these
+#![allow(dead_code)]
+
+use chess::pgn::Game;
+use chess::pieces::Move;
+use chess::position::Position;
+
+pub fn position(fen: &str) -> Position {
+    Position::from_fen(fen).unwrap()
+}
+
+// Position reached by playing the UCI moves from the given start
+pub fn after(fen: &str, moves: &[&str]) -> Position {
+    let mut position = position(fen);
+    play(&mut position, moves);
+    position
+}
+
+// Plays the UCI moves and returns the hash of every position along the way, the start included
+pub fn play(position: &mut Position, moves: &[&str]) -> Vec<u64> {
+    let mut hashes = vec![position.hash];
+    for uci in moves {
+        let mv = Move::from_uci(position, uci).unwrap();
+        position.play_move(&mv).unwrap();
+        hashes.push(position.hash);
+    }
+    hashes
+}
+
+// Game from the given start with the UCI moves recorded, as the window does
+pub fn game(fen: &str, moves: &[&str]) -> Game {
+    let mut position = position(fen);
+    let mut game = Game::new(position);
+    for uci in moves {
+        let mv = Move::from_uci(&position, uci).unwrap();
+        game.record(&position, mv);
+        position.play_move(&mv).unwrap();
+    }
+    game
+}
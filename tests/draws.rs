@@ -0,0 +1,107 @@
+// Repetition and move-count draws, which players claim or the rules impose
+extern crate chess;
+
+mod common;
+
+use chess::pieces::PieceColor;
+use chess::position::Position;
+use chess::state::{claimable_draw, DrawReason, State};
+use common::play;
+
+fn state_of(position: &Position) -> State {
+    State::Play.change_state(position).unwrap().0
+}
+
+#[test]
+fn threefold_repetition_can_be_claimed() {
+    let mut position = Position::new();
+    let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+
+    let hashes = play(&mut position, &shuffle);
+    assert_eq!(claimable_draw(&position, &hashes), None);
+
+    let mut more = play(&mut position, &shuffle);
+    let mut all = hashes.clone();
+    all.append(&mut more.split_off(1));
+    assert_eq!(claimable_draw(&position, &all), Some(DrawReason::ThreefoldRepetition));
+}
+
+#[test]
+fn captures_break_repetitions() {
+    // The same layout three times, but a capture or pawn move since the first one means it can't come back
+    let hashes = |position: &Position| vec![position.hash, 1, position.hash, 2, position.hash];
+    let position = Position::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 4 10").unwrap();
+    assert_eq!(claimable_draw(&position, &hashes(&position)), Some(DrawReason::ThreefoldRepetition));
+
+    let position = Position::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 2 10").unwrap();
+    assert_eq!(claimable_draw(&position, &hashes(&position)), None);
+}
+
+#[test]
+fn fifty_moves_can_be_claimed() {
+    let position = Position::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 99 80").unwrap();
+    assert_eq!(claimable_draw(&position, &[position.hash]), None);
+
+    let position = Position::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 100 80").unwrap();
+    assert_eq!(claimable_draw(&position, &[position.hash]), Some(DrawReason::FiftyMoveRule));
+    // Only a claim ends the game this early
+    assert_eq!(state_of(&position), State::Play);
+}
+
+#[test]
+fn seventy_five_moves_end_the_game() {
+    let position = Position::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 150 100").unwrap();
+    assert_eq!(state_of(&position), State::Draw(DrawReason::SeventyFiveMoveRule));
+}
+
+#[test]
+fn checkmate_on_the_seventy_fifth_move_stands() {
+    let mut position = Position::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 149 100").unwrap();
+    play(&mut position, &["h1h8"]);
+    assert_eq!(position.halfmove_clock, 150);
+    // Rh8+ isn't mate with the King free to step up, so the game is drawn...
+    assert_eq!(state_of(&position), State::Draw(DrawReason::SeventyFiveMoveRule));
+
+    // ...but a mating move on the same count wins
+    let mut position = Position::from_fen("k7/8/1K6/8/8/8/8/7R w - - 149 100").unwrap();
+    play(&mut position, &["h1h8"]);
+    assert_eq!(state_of(&position), State::Checkmate(PieceColor::White));
+}
+
+#[test]
+fn dead_positions_are_drawn() {
+    for fen in [
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/1N2K3 b - - 0 1",
+        // Bishops on c1 and f8 both stand on dark squares
+        "4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1",
+    ] {
+        let position = Position::from_fen(fen).unwrap();
+        assert!(position.has_insufficient_material(), "{fen}");
+        assert_eq!(state_of(&position), State::Draw(DrawReason::InsufficientMaterial), "{fen}");
+    }
+}
+
+#[test]
+fn mating_material_plays_on() {
+    for fen in [
+        "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1",
+        // Bishops on opposite colors can still mate in a corner with the other side's help
+        "4k1b1/8/8/8/8/8/8/2B1K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/4K2R w - - 0 1",
+    ] {
+        let position = Position::from_fen(fen).unwrap();
+        assert!(!position.has_insufficient_material(), "{fen}");
+    }
+}
+
+#[test]
+fn capturing_the_last_mating_piece_draws() {
+    // The King takes the Rook, leaving King and Knight against a bare King
+    let mut position = Position::from_fen("4k3/8/8/8/8/8/8/3rK1N1 w - - 5 40").unwrap();
+    play(&mut position, &["e1d1"]);
+    assert_eq!(state_of(&position), State::Draw(DrawReason::InsufficientMaterial));
+}
@@ -0,0 +1,66 @@
+// Static evaluation of positions
+extern crate chess;
+
+mod common;
+
+use chess::eval::{evaluate, evaluate_relative};
+use chess::position::Position;
+use common::position;
+
+fn score(fen: &str) -> i32 {
+    evaluate(&position(fen))
+}
+
+// The same position with the colors swapped and the board turned around, so White's pieces stand where Black's did
+fn mirror(fen: &str) -> String {
+    let fields: Vec<&str> = fen.split(' ').collect();
+    let swap_case = |text: &str| -> String {
+        text.chars().map(|c| if c.is_ascii_uppercase() { c.to_ascii_lowercase() } else { c.to_ascii_uppercase() }).collect()
+    };
+
+    let board = fields[0].split('/').rev().map(swap_case).collect::<Vec<String>>().join("/");
+    let side = if fields[1] == "w" { "b" } else { "w" };
+    let mut castling: Vec<char> = swap_case(fields[2]).chars().collect();
+    // KQkq order: White's rights come first
+    castling.sort_by_key(|c| (c.is_ascii_lowercase(), *c != 'K' && *c != 'k'));
+    let castling: String = castling.into_iter().collect();
+    let en_passant = fields[3].replace('3', "x").replace('6', "3").replace('x', "6");
+    format!("{board} {side} {castling} {en_passant} {} {}", fields[4], fields[5])
+}
+
+#[test]
+fn start_position_is_even() {
+    assert_eq!(evaluate(&Position::new()), 0);
+    assert_eq!(evaluate_relative(&Position::new()), 0);
+}
+
+#[test]
+fn mirrored_positions_score_the_other_way() {
+    for fen in [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/ppp1pppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 3",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ] {
+        let mirrored = mirror(fen);
+        assert_eq!(score(&mirrored), -score(fen), "{fen} against {mirrored}");
+        // Each side to move sees its own position the same way
+        let relative = |fen: &str| evaluate_relative(&position(fen));
+        assert_eq!(relative(&mirrored), relative(fen), "{fen} against {mirrored}");
+    }
+}
+
+#[test]
+fn passed_pawns_count_for_more_in_the_endgame() {
+    // The e6 pawn is passed with Black's pawn on a7, and blocked with it on e7
+    let passed = score("4k3/p7/4P3/8/8/8/8/4K3 w - - 0 1");
+    let blocked = score("4k3/4p3/4P3/8/8/8/8/4K3 w - - 0 1");
+    assert!(passed > blocked, "{passed} against {blocked}");
+
+    // With the Queens still on, the game is nearer the middlegame and the same pawn is worth less
+    let passed_with_queens = score("3qk3/p7/4P3/8/8/8/8/3QK3 w - - 0 1");
+    let blocked_with_queens = score("3qk3/4p3/4P3/8/8/8/8/3QK3 w - - 0 1");
+    assert!(passed_with_queens > blocked_with_queens, "{passed_with_queens} against {blocked_with_queens}");
+    assert!(passed - blocked > passed_with_queens - blocked_with_queens);
+}
@@ -0,0 +1,119 @@
+// Reading and writing positions in Forsyth-Edwards Notation
+extern crate chess;
+
+use chess::fen::FenError;
+use chess::pieces::{PieceColor, Point};
+use chess::position::Position;
+
+fn error(fen: &str) -> Option<FenError> {
+    Position::from_fen(fen).err()
+}
+
+#[test]
+fn positions_write_back_the_same() {
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        // Black can take en passant on d3
+        "rnbqkbnr/ppp1pppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 3",
+        // Only some castling rights left
+        "r3k2r/8/8/8/8/8/8/R3K2R w Kq - 12 40",
+        "r3k2r/8/8/8/8/8/8/R3K2R b Q - 3 22",
+    ] {
+        let position = Position::from_fen(fen).unwrap();
+        assert_eq!(position.to_fen(), fen);
+    }
+}
+
+#[test]
+fn start_position_matches_the_default() {
+    let position = Position::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+    assert!(position == Position::new());
+}
+
+#[test]
+fn move_counters_may_be_left_out() {
+    let position = Position::from_fen("4k3/8/8/8/8/8/8/4K3 b - -").unwrap();
+    assert_eq!(position.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+}
+
+#[test]
+fn field_count() {
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w - - 0"), Some(FenError::FieldCount(5)));
+    assert_eq!(error(""), Some(FenError::FieldCount(0)));
+}
+
+#[test]
+fn rank_count() {
+    assert_eq!(error("4k3/8/8/8/8/8/4K3 w - - 0 1"), Some(FenError::RankCount(7)));
+}
+
+#[test]
+fn rank_length() {
+    // Short of 8 squares, past 8 squares, and a piece after the 8th square
+    assert_eq!(error("4k3/8/8/8/8/8/7/4K3 w - - 0 1"), Some(FenError::RankLength(2)));
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K4 w - - 0 1"), Some(FenError::RankLength(1)));
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K3P w - - 0 1"), Some(FenError::RankLength(1)));
+}
+
+#[test]
+fn invalid_piece() {
+    assert_eq!(error("4k3/8/8/8/8/8/8/4X3 w - - 0 1"), Some(FenError::InvalidPiece('X')));
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K0 w - - 0 1"), Some(FenError::InvalidPiece('0')));
+}
+
+#[test]
+fn invalid_side_to_move() {
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K3 W - - 0 1"), Some(FenError::InvalidSideToMove("W".to_string())));
+}
+
+#[test]
+fn invalid_castling() {
+    assert_eq!(error("r3k2r/8/8/8/8/8/8/R3K2R w KX - 0 1"), Some(FenError::InvalidCastling("KX".to_string())));
+    assert_eq!(error("r3k2r/8/8/8/8/8/8/R3K2R w KK - 0 1"), Some(FenError::InvalidCastling("KK".to_string())));
+    // Rights need the King and the Rook on their home squares
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w KQkq - 0 1"), Some(FenError::InvalidCastling("KQkq".to_string())));
+    assert_eq!(error("r3k2r/8/8/8/8/8/8/R3K1R1 w K - 0 1"), Some(FenError::InvalidCastling("K".to_string())));
+    assert_eq!(error("r3k2r/8/8/8/8/8/8/R4K1R w Q - 0 1"), Some(FenError::InvalidCastling("Q".to_string())));
+    assert_eq!(error("1r2k2r/8/8/8/8/8/8/R3K2R w Kkq - 0 1"), Some(FenError::InvalidCastling("Kkq".to_string())));
+    assert_eq!(error("1r2k2r/8/8/8/8/8/8/R3K2R w KQk - 0 1"), None);
+}
+
+#[test]
+fn invalid_en_passant() {
+    // Not a square, the wrong rank for the side that moved, and no pawn in front of the square
+    assert_eq!(error("4k3/8/8/8/4P3/8/8/4K3 b - e9 0 1"), Some(FenError::InvalidEnPassant("e9".to_string())));
+    assert_eq!(error("4k3/8/8/8/4P3/8/8/4K3 b - e6 0 1"), Some(FenError::InvalidEnPassant("e6".to_string())));
+    assert_eq!(error("4k3/8/8/8/4P3/8/8/4K3 b - d3 0 1"), Some(FenError::InvalidEnPassant("d3".to_string())));
+    // Something standing on the skipped square, or on the square the pawn would have started from
+    assert_eq!(error("4k3/8/4N3/3Pp3/8/8/8/4K3 w - e6 0 1"), Some(FenError::InvalidEnPassant("e6".to_string())));
+    assert_eq!(error("4k3/4n3/8/3Pp3/8/8/8/4K3 w - e6 0 1"), Some(FenError::InvalidEnPassant("e6".to_string())));
+    assert_eq!(error("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1"), None);
+}
+
+#[test]
+fn invalid_move_counters() {
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w - - x 1"), Some(FenError::InvalidHalfmoveClock("x".to_string())));
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w - - -1 1"), Some(FenError::InvalidHalfmoveClock("-1".to_string())));
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w - - 0 0"), Some(FenError::InvalidFullmoveNumber("0".to_string())));
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w - - 0 y"), Some(FenError::InvalidFullmoveNumber("y".to_string())));
+}
+
+#[test]
+fn king_count() {
+    assert_eq!(error("4k3/8/8/8/8/8/8/8 w - - 0 1"), Some(FenError::KingCount(PieceColor::White, 0)));
+    assert_eq!(error("4k3/8/8/8/8/8/8/3KK3 w - - 0 1"), Some(FenError::KingCount(PieceColor::White, 2)));
+    assert_eq!(error("8/8/8/8/8/8/8/4K3 w - - 0 1"), Some(FenError::KingCount(PieceColor::Black, 0)));
+}
+
+#[test]
+fn pawn_on_back_rank() {
+    let point = Point::from_algebraic("a8").unwrap();
+    assert_eq!(error("P3k3/8/8/8/8/8/8/4K3 w - - 0 1"), Some(FenError::PawnOnBackRank(point)));
+}
+
+#[test]
+fn opponent_in_check() {
+    // The same Rook is fine on h1, but on h8 it attacks the Black King with White to move
+    assert_eq!(error("4k3/8/8/8/8/8/8/4K2R w - - 0 1"), None);
+    assert_eq!(error("4k2R/8/8/8/8/8/8/4K3 w - - 0 1"), Some(FenError::OpponentInCheck));
+}
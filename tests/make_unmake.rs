@@ -0,0 +1,50 @@
+// Every move played with make_move and taken back with unmake_move has to leave the position exactly as it was,
+// hash included, so search and takebacks never drift from the real game
+extern crate chess;
+
+use chess::position::Position;
+use chess::zobrist;
+
+// Covers castling both ways, en passant, promotions with and without a capture, and checks
+const POSITIONS: [&str; 4] = [
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+];
+
+fn walk(position: &mut Position, depth: u32) {
+    if depth == 0 {
+        return;
+    }
+    for mv in position.generate_moves() {
+        let before = *position;
+        let undo = position.make_move(&mv).unwrap();
+        assert_eq!(position.hash, zobrist::hash(position), "hash after {} from {}", mv.uci(), before.to_fen());
+
+        walk(position, depth - 1);
+        position.unmake_move(&undo);
+        assert!(*position == before, "unmaking {} from {} gave {}", mv.uci(), before.to_fen(), position.to_fen());
+    }
+}
+
+#[test]
+fn unmake_restores_the_position() {
+    for fen in POSITIONS {
+        let mut position = Position::from_fen(fen).unwrap();
+        walk(&mut position, 3);
+    }
+}
+
+#[test]
+fn undo_records_the_captured_piece() {
+    // En passant takes the pawn beside the target, not one on it
+    let mut position = Position::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2").unwrap();
+    let mv = chess::pieces::Move::from_uci(&position, "e5d6").unwrap();
+    let undo = position.make_move(&mv).unwrap();
+
+    let (point, piece) = undo.captured.unwrap();
+    assert_eq!(point.algebraic(), "d5");
+    assert_eq!(piece.piece_type, chess::pieces::Type::Pawn);
+    assert_eq!(undo.en_passant.map(|point| point.algebraic()), Some("d6".to_string()));
+}
@@ -0,0 +1,148 @@
+// Writing and reading moves in SAN and UCI notation
+extern crate chess;
+
+mod common;
+
+use chess::notation::NotationError;
+use chess::pieces::{Move, Type};
+use chess::position::Position;
+use common::position;
+
+// Covers castling both ways, en passant, promotions with and without a capture, checks and pieces needing disambiguation
+const POSITIONS: [&str; 5] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "4k3/8/8/R7/8/Q7/8/Q1Q1K1NN w - - 0 1",
+];
+
+fn san(fen: &str, uci: &str) -> String {
+    let position = position(fen);
+    Move::from_uci(&position, uci).unwrap().san(&position)
+}
+
+#[test]
+fn san_names_the_origin_only_when_needed() {
+    // Knights on b1 and f1 can both reach d2, and are told apart by file
+    assert_eq!(san("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1", "b1d2"), "Nbd2");
+    assert_eq!(san("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1", "f1d2"), "Nfd2");
+    // Rooks on the same file are told apart by rank
+    assert_eq!(san("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", "a1a3"), "R1a3");
+    assert_eq!(san("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", "a5a3"), "R5a3");
+    // One rival shares the file and another the rank, so it takes both
+    assert_eq!(san("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1", "a1b2"), "Qa1b2");
+    // ...while the one on a3 shares only the file with the others
+    assert_eq!(san("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1", "a3b2"), "Q3b2");
+    // Only one Knight can reach d2 here
+    assert_eq!(san("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1", "b1d2"), "Nd2");
+}
+
+#[test]
+fn san_reads_disambiguated_moves() {
+    let queens = position("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1");
+    assert_eq!(Move::from_san(&queens, "Qa1b2").unwrap().uci(), "a1b2");
+    assert_eq!(Move::from_san(&queens, "Qcb2").unwrap().uci(), "c1b2");
+    assert_eq!(Move::from_san(&queens, "Q3b2").unwrap().uci(), "a3b2");
+    assert_eq!(Move::from_san(&queens, "Qab2"), Err(NotationError::Ambiguous("Qab2".to_string())));
+    assert_eq!(Move::from_san(&queens, "Qb2"), Err(NotationError::Ambiguous("Qb2".to_string())));
+}
+
+#[test]
+fn capture_marker_has_to_be_right() {
+    let start = Position::new();
+    assert_eq!(Move::from_san(&start, "Nxf3"), Err(NotationError::WrongCapture("Nxf3".to_string())));
+
+    let scandinavian = position("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2");
+    assert_eq!(Move::from_san(&scandinavian, "exd5").unwrap().uci(), "e4d5");
+    assert_eq!(Move::from_san(&scandinavian, "ed5"), Err(NotationError::WrongCapture("ed5".to_string())));
+
+    // En passant lands on an empty square but still takes a pawn
+    let en_passant = position("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2");
+    assert_eq!(san("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2", "e5d6"), "exd6");
+    assert_eq!(Move::from_san(&en_passant, "exd6").unwrap().uci(), "e5d6");
+}
+
+#[test]
+fn check_marker_has_to_be_right() {
+    let start = Position::new();
+    assert_eq!(Move::from_san(&start, "e4+"), Err(NotationError::WrongCheckMarker("e4+".to_string())));
+    assert_eq!(Move::from_san(&start, "e4#"), Err(NotationError::WrongCheckMarker("e4#".to_string())));
+
+    // Fool's mate: Qh4 is checkmate, not just check
+    let fools_mate = position("rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq g3 0 2");
+    assert_eq!(san("rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq g3 0 2", "d8h4"), "Qh4#");
+    assert!(Move::from_san(&fools_mate, "Qh4#").is_ok());
+    assert!(Move::from_san(&fools_mate, "Qh4").is_ok());
+    assert_eq!(Move::from_san(&fools_mate, "Qh4+"), Err(NotationError::WrongCheckMarker("Qh4+".to_string())));
+    assert_eq!(Move::from_san(&fools_mate, "Qh4#+"), Err(NotationError::WrongCheckMarker("Qh4#+".to_string())));
+    // Annotations aren't markers
+    assert!(Move::from_san(&fools_mate, "Qh4#!!").is_ok());
+}
+
+#[test]
+fn promotions() {
+    let fen = "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+    let pawn = position(fen);
+    assert_eq!(san(fen, "a7a8q"), "a8=Q");
+    assert_eq!(san(fen, "a7b8n"), "axb8=N");
+    assert_eq!(san(fen, "a7b8r"), "axb8=R+");
+
+    let queen = Move::from_san(&pawn, "a8=Q").unwrap();
+    assert_eq!(queen.promotion, Some(Type::Queen));
+    assert_eq!(queen.uci(), "a7a8q");
+    assert_eq!(Move::from_san(&pawn, "a8Q"), Ok(queen));
+    assert_eq!(Move::from_uci(&pawn, "a7a8q"), Ok(queen));
+    assert_eq!(Move::from_san(&pawn, "axb8=R+").unwrap().uci(), "a7b8r");
+
+    // UCI writes the piece in lower case, and a pawn reaching the last rank has to become something
+    assert_eq!(Move::from_uci(&pawn, "a7a8Q"), Err(NotationError::InvalidPiece('Q')));
+    assert_eq!(Move::from_uci(&pawn, "a7a8x"), Err(NotationError::InvalidPiece('x')));
+    assert_eq!(Move::from_uci(&pawn, "a7a8k"), Err(NotationError::Illegal("a7a8k".to_string())));
+    assert_eq!(Move::from_uci(&pawn, "a7a8"), Err(NotationError::Illegal("a7a8".to_string())));
+    assert_eq!(Move::from_san(&pawn, "a8=K"), Err(NotationError::Illegal("a8=K".to_string())));
+}
+
+#[test]
+fn castling_with_letters_or_zeros() {
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    let both_ways = position(fen);
+    assert_eq!(san(fen, "e1g1"), "O-O");
+    assert_eq!(san(fen, "e1c1"), "O-O-O");
+
+    for (san, uci) in [("O-O", "e1g1"), ("0-0", "e1g1"), ("O-O-O", "e1c1"), ("0-0-0", "e1c1")] {
+        assert_eq!(Move::from_san(&both_ways, san).unwrap().uci(), uci, "{san}");
+    }
+
+    // Black castles on its own rank
+    let black = position("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1");
+    assert_eq!(Move::from_san(&black, "0-0").unwrap().uci(), "e8g8");
+    assert_eq!(Move::from_san(&black, "O-O-O").unwrap().uci(), "e8c8");
+
+    // Without the rights it's no longer legal
+    let no_rights = position("r3k2r/8/8/8/8/8/8/R3K2R w kq - 0 1");
+    assert_eq!(Move::from_san(&no_rights, "O-O"), Err(NotationError::Illegal("O-O".to_string())));
+}
+
+#[test]
+fn malformed_text_is_rejected() {
+    let start = Position::new();
+    assert_eq!(Move::from_uci(&start, "e2"), Err(NotationError::Malformed("e2".to_string())));
+    assert_eq!(Move::from_uci(&start, "e2e9"), Err(NotationError::InvalidSquare("e9".to_string())));
+    assert_eq!(Move::from_uci(&start, "e2e5"), Err(NotationError::Illegal("e2e5".to_string())));
+    assert_eq!(Move::from_san(&start, "+"), Err(NotationError::Malformed("+".to_string())));
+    assert_eq!(Move::from_san(&start, "Zf3"), Err(NotationError::InvalidPiece('Z')));
+    assert_eq!(Move::from_san(&start, "Ne4"), Err(NotationError::Illegal("Ne4".to_string())));
+}
+
+#[test]
+fn every_legal_move_reads_back_the_same() {
+    for fen in POSITIONS {
+        let position = position(fen);
+        for mv in position.generate_moves() {
+            let san = mv.san(&position);
+            assert_eq!(Move::from_san(&position, &san), Ok(mv), "{san} in {fen}");
+            assert_eq!(Move::from_uci(&position, &mv.uci()), Ok(mv), "{} in {fen}", mv.uci());
+        }
+    }
+}
@@ -0,0 +1,79 @@
+// Reading and writing games in PGN
+extern crate chess;
+
+mod common;
+
+use chess::pgn::Game;
+use common::game;
+
+#[test]
+fn exported_games_read_back_the_same() {
+    let games = [
+        // Castling both ways, captures, checks and a promotion
+        game(
+            "r3k2r/6P1/8/8/8/8/8/R3K2R w KQkq - 0 1",
+            &["e1g1", "e8c8", "g7h8q", "d8h8", "a1a8", "c8d7", "a8h8"],
+        ),
+        // Starts with Black's move, which is numbered "1..."
+        game("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", &["c7c5", "g1f3", "d7d6", "d2d4", "c5d4"]),
+        game(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            &["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "b5c6", "d7c6", "e1g1", "f7f6"],
+        ),
+    ];
+
+    for mut exported in games {
+        exported.set_tag("Result", "*");
+        let pgn = exported.to_pgn();
+        let imported = Game::from_pgn(&pgn).unwrap();
+
+        assert_eq!(imported.tags, exported.tags, "{pgn}");
+        assert!(imported.start == exported.start, "{pgn}");
+        assert_eq!(imported.moves, exported.moves, "{pgn}");
+        assert_eq!(imported.san, exported.san, "{pgn}");
+        assert_eq!(imported.to_pgn(), pgn);
+    }
+}
+
+#[test]
+fn export_numbers_and_tags_the_moves() {
+    let mut exported = game("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &["e2e4", "e7e5", "g1f3"]);
+    exported.set_tag("Result", "*");
+    let pgn = exported.to_pgn();
+    assert!(pgn.starts_with("[Event \"Casual Game\"]\n"), "{pgn}");
+    assert!(!pgn.contains("[FEN "), "{pgn}");
+    assert!(pgn.ends_with("\n\n1. e4 e5 2. Nf3 *\n"), "{pgn}");
+
+    let from_black = game("4k3/8/8/8/8/8/8/4K2R b K - 0 30", &["e8d7", "e1g1"]);
+    let pgn = from_black.to_pgn();
+    assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/4K2R b K - 0 30\"]\n"), "{pgn}");
+    assert!(pgn.ends_with("30... Kd7 31. O-O *\n"), "{pgn}");
+}
+
+#[test]
+fn castling_may_be_written_with_zeros() {
+    let pgn = "1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. 0-0 Nf6 5. d3 d6 6. Bg5 Qe7 7. Nc3 Bd7 8. Qd2 0-0-0 *";
+    let imported = Game::from_pgn(pgn).unwrap();
+    assert_eq!(imported.san[6], "O-O");
+    assert_eq!(imported.san[15], "O-O-O");
+
+    // Glued to its move number as well
+    let imported = Game::from_pgn("1.e4 e5 2.Nf3 Nc6 3.Bc4 Bc5 4.0-0 *").unwrap();
+    assert_eq!(imported.san.last().unwrap(), "O-O");
+}
+
+#[test]
+fn import_skips_everything_but_the_main_line() {
+    let pgn = "[Event \"Club night\"]\n[White \"A \\\"Quoted\\\" Name\"]\n\n\
+               1.e4 {best by test} e5 (1...c5 2.Nf3) 2.Nf3!? $1 Nc6 ; the usual\n3.Bb5 a6 1-0\n\n1. d4 *";
+    let imported = Game::from_pgn(pgn).unwrap();
+    assert_eq!(imported.tag("Event"), Some("Club night"));
+    assert_eq!(imported.tag("White"), Some("A \"Quoted\" Name"));
+    assert_eq!(imported.san, ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+}
+
+#[test]
+fn import_reports_bad_moves() {
+    assert!(Game::from_pgn("1. e4 e5 2. Ke3 *").err().unwrap().starts_with("Move 2:"));
+    assert!(Game::from_pgn("1. e4 (1. d4 *").is_err());
+}
@@ -0,0 +1,55 @@
+// What the search makes of positions near the end of the game
+extern crate chess;
+
+mod common;
+
+use chess::position::Position;
+use chess::search::{search, Limits, Report, MATE_SCORE};
+use chess::transposition::TranspositionTable;
+
+use common::{play, position};
+
+use std::sync::atomic::AtomicBool;
+
+// Best move in UCI notation and the score of the last finished iteration
+fn best(fen: &str, depth: u32) -> (String, i32) {
+    best_after(&position(fen), &[], depth)
+}
+
+// Same, for a position reached through the game with the given hashes
+fn best_after(position: &Position, history: &[u64], depth: u32) -> (String, i32) {
+    let mut table = TranspositionTable::default();
+    let mut score = 0;
+    let limits = Limits { depth: Some(depth), movetime: None };
+    let mv = search(position, history, limits, &mut table, &AtomicBool::new(false), |report: &Report| score = report.score).unwrap();
+    (mv.uci(), score)
+}
+
+#[test]
+fn finds_mate_in_one() {
+    assert_eq!(best("k7/8/1K6/8/8/8/8/7R w - - 0 1", 3), ("h1h8".to_string(), MATE_SCORE - 1));
+}
+
+#[test]
+fn mate_on_the_hundredth_ply_still_wins() {
+    // Rh8# is the hundredth ply without a capture or pawn move, which doesn't stop it being mate
+    assert_eq!(best("k7/8/1K6/8/8/8/8/7R w - - 99 80", 3), ("h1h8".to_string(), MATE_SCORE - 1));
+}
+
+#[test]
+fn fifty_move_limit_draws_anything_else() {
+    // A Rook up, but every move reaches the limit without mating
+    assert_eq!(best("4k3/8/8/8/8/8/8/4K2R w - - 99 80", 3).1, 0);
+}
+
+#[test]
+fn heading_back_into_an_earlier_position_draws() {
+    // A Queen down, White's only hope is stepping back to g1, which the game has already seen with Black to move
+    let mut position = position("3qk3/8/8/8/8/8/8/7K w - - 10 40");
+    assert!(best_after(&position, &[], 3).1 < -500);
+
+    // The history stops short of the position being searched
+    let hashes = play(&mut position, &["h1g1", "d8d7", "g1h1", "d7d8"]);
+    let (_, history) = hashes.split_last().unwrap();
+    assert_eq!(best_after(&position, history, 3), ("h1g1".to_string(), 0));
+}
@@ -0,0 +1,89 @@
+// State transitions worked out from positions alone, with no window involved
+extern crate chess;
+
+mod common;
+
+use chess::pieces::{Move, PieceColor, Point, Type};
+use chess::state::{DrawReason, State};
+use common::{after, position};
+
+fn squares(points: &[Point]) -> Vec<String> {
+    let mut names: Vec<String> = points.iter().map(|point| point.algebraic()).collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn quiet_move_keeps_playing() {
+    let position = after("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &["e2e4"]);
+    assert_eq!(State::Play.change_state(&position).unwrap(), (State::Play, vec![]));
+}
+
+#[test]
+fn check_lists_the_attacker() {
+    let position = after("rnbqkbnr/ppp2ppp/3p4/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3", &["f1b5"]);
+    let (state, predators) = State::Play.change_state(&position).unwrap();
+    assert_eq!(state, State::Check);
+    assert_eq!(squares(&predators), ["b5"]);
+}
+
+#[test]
+fn double_check_lists_both_attackers() {
+    // The Knight steps away from the Rook's file and checks as well
+    let position = after("4k3/8/8/8/4N3/8/8/4R1K1 w - - 0 1", &["e4f6"]);
+    let (state, predators) = State::Check.change_state(&position).unwrap();
+    assert_eq!(state, State::Check);
+    assert_eq!(squares(&predators), ["e1", "f6"]);
+}
+
+#[test]
+fn checkmate_names_the_winner() {
+    let position = after("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &["f2f3", "e7e5", "g2g4", "d8h4"]);
+    let (state, predators) = State::Play.change_state(&position).unwrap();
+    assert_eq!(state, State::Checkmate(PieceColor::Black));
+    assert_eq!(squares(&predators), ["h4"]);
+}
+
+#[test]
+fn promotion_picked_in_the_window_can_mate() {
+    // The window moves the pawn first and asks what it becomes afterwards. Until then Black has no moves and isn't in check...
+    let mut position = position("7k/4PK2/5N2/8/8/8/8/8 w - - 0 1");
+    let mv = Move { from: Point::from_algebraic("e7").unwrap(), to: Point::from_algebraic("e8").unwrap(), promotion: None };
+    position.make_move(&mv).unwrap();
+    let (state, _) = State::Play.change_state(&position).unwrap();
+    assert_eq!(state, State::Stalemate);
+
+    // ...but the Queen it turns into gives mate, which only a fresh look at the position sees
+    position.promote(&Point::from_algebraic("e8").unwrap(), Type::Queen).unwrap();
+    assert_eq!(state.change_state(&position).unwrap().0, State::Stalemate);
+    let (state, predators) = State::Play.change_state(&position).unwrap();
+    assert_eq!(state, State::Checkmate(PieceColor::White));
+    assert_eq!(squares(&predators), ["e8"]);
+}
+
+#[test]
+fn stalemate_when_nothing_is_legal() {
+    let position = after("7k/8/6Q1/8/8/8/8/K7 w - - 0 1", &["g6f7"]);
+    assert_eq!(State::Play.change_state(&position).unwrap().0, State::Stalemate);
+}
+
+#[test]
+fn draws_carry_their_reason() {
+    let position = after("4k3/8/8/8/8/8/8/4K2R w - - 150 100", &[]);
+    assert_eq!(State::Play.change_state(&position).unwrap().0, State::Draw(DrawReason::SeventyFiveMoveRule));
+}
+
+#[test]
+fn finished_games_stay_finished() {
+    // A claimed draw isn't undone by the next position looking playable
+    let position = after("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &[]);
+    let claimed = State::Draw(DrawReason::ThreefoldRepetition);
+    assert_eq!(claimed.change_state(&position).unwrap(), (claimed, vec![]));
+}
+
+#[test]
+fn missing_king_is_an_error() {
+    let mut position = after("4k3/8/8/8/8/8/8/4K3 w - - 0 1", &[]);
+    position.take(&Point::from_algebraic("e1").unwrap());
+    assert!(State::Play.change_state(&position).is_err());
+}
@@ -0,0 +1,52 @@
+// Which results the transposition table keeps when two positions want the same slot
+extern crate chess;
+
+use chess::transposition::{Bound, TranspositionTable};
+
+// Hashes differing only in their high bits land in the same slot of any table that fits in memory
+const FIRST: u64 = 0x1234;
+const SECOND: u64 = FIRST | 1 << 48;
+
+#[test]
+fn probe_needs_the_full_hash() {
+    let mut table = TranspositionTable::new(1);
+    table.store(FIRST, 4, 25, Bound::Exact, None);
+    let entry = table.probe(FIRST).unwrap();
+    assert_eq!((entry.key, entry.depth, entry.score, entry.bound), (FIRST, 4, 25, Bound::Exact));
+    assert!(table.probe(SECOND).is_none());
+}
+
+#[test]
+fn shallower_result_keeps_a_deeper_one_from_the_same_search() {
+    let mut table = TranspositionTable::new(1);
+    table.new_search();
+    table.store(FIRST, 6, 25, Bound::Exact, None);
+    table.store(SECOND, 2, -40, Bound::Lower, None);
+    assert_eq!(table.probe(FIRST).unwrap().depth, 6);
+    assert!(table.probe(SECOND).is_none());
+
+    // As deep is enough to take the slot
+    table.store(SECOND, 6, -40, Bound::Lower, None);
+    assert!(table.probe(FIRST).is_none());
+    assert_eq!(table.probe(SECOND).unwrap().score, -40);
+}
+
+#[test]
+fn results_from_an_earlier_search_are_replaced() {
+    let mut table = TranspositionTable::new(1);
+    table.new_search();
+    table.store(FIRST, 6, 25, Bound::Exact, None);
+    table.new_search();
+    table.store(SECOND, 2, -40, Bound::Lower, None);
+    assert!(table.probe(FIRST).is_none());
+    assert_eq!(table.probe(SECOND).unwrap().depth, 2);
+}
+
+#[test]
+fn same_position_is_always_updated() {
+    let mut table = TranspositionTable::new(1);
+    table.store(FIRST, 6, 25, Bound::Exact, None);
+    table.store(FIRST, 2, 10, Bound::Upper, None);
+    let entry = table.probe(FIRST).unwrap();
+    assert_eq!((entry.depth, entry.score, entry.bound), (2, 10, Bound::Upper));
+}